@@ -0,0 +1,528 @@
+//! `oauthsign login`: the OAuth 1 three-legged flow or the OAuth 2
+//! authorization code flow with PKCE, saving the credentials to a profile.
+use oauthsign::credentials::{ClientCredentials, ConsumerCredentials, Profile, ProfileStore};
+use oauthsign::Secret;
+use std::path::PathBuf;
+use std::process;
+
+const USAGE: &str = "\
+Usage: oauthsign login [OPTIONS] <PROFILE>
+
+Obtains credentials and saves them to PROFILE along with the options below,
+so that `oauthsign login <PROFILE>` alone logs in again.
+
+OAuth 1 (three-legged flow):
+  -k, --consumer-key <KEY>
+  -s, --consumer-secret <SECRET>
+      --request-token-url <URL>
+      --authorize-url <URL>
+      --access-token-url <URL>
+      --pin                             Enter the PIN shown by the provider instead of
+                                        receiving the callback on a loopback address
+
+OAuth 2 (authorization code flow with PKCE):
+      --client-id <ID>
+      --client-secret <SECRET>          Omit for public clients
+      --issuer <URL>                    Discover the endpoints of the issuer
+      --authorization-endpoint <URL>
+      --token-endpoint <URL>
+      --scope <SCOPE>
+
+      --no-browser                      Print the authorization URL without opening it
+      --profile-file <PATH>             [env: OAUTHSIGN_PROFILE_FILE, default: ~/.oauthsign.toml]
+  -h, --help
+";
+
+#[derive(Debug, Default, PartialEq)]
+struct LoginOptions {
+    profile: String,
+    profile_file: Option<PathBuf>,
+    consumer_key: Option<String>,
+    consumer_secret: Option<String>,
+    request_token_url: Option<String>,
+    authorize_url: Option<String>,
+    access_token_url: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    issuer: Option<String>,
+    authorization_endpoint: Option<String>,
+    token_endpoint: Option<String>,
+    scope: Option<String>,
+    pin: bool,
+    no_browser: bool,
+}
+
+impl LoginOptions {
+    /// Parse the command line, `None` when help is requested.
+    fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let mut options = LoginOptions::default();
+        let mut profile = None;
+        let mut profile_file = None;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let target = match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--pin" => {
+                    options.pin = true;
+                    continue;
+                }
+                "--no-browser" => {
+                    options.no_browser = true;
+                    continue;
+                }
+                "--profile-file" => &mut profile_file,
+                "-k" | "--consumer-key" => &mut options.consumer_key,
+                "-s" | "--consumer-secret" => &mut options.consumer_secret,
+                "--request-token-url" => &mut options.request_token_url,
+                "--authorize-url" => &mut options.authorize_url,
+                "--access-token-url" => &mut options.access_token_url,
+                "--client-id" => &mut options.client_id,
+                "--client-secret" => &mut options.client_secret,
+                "--issuer" => &mut options.issuer,
+                "--authorization-endpoint" => &mut options.authorization_endpoint,
+                "--token-endpoint" => &mut options.token_endpoint,
+                "--scope" => &mut options.scope,
+                s if s.starts_with('-') && s.len() > 1 => {
+                    return Err(format!("unknown option: {}", s))
+                }
+                _ if profile.is_none() => {
+                    profile = Some(arg.clone());
+                    continue;
+                }
+                _ => return Err(format!("unexpected argument: {}", arg)),
+            };
+            *target = Some(
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{} requires a value", arg))?,
+            );
+        }
+        options.profile = profile.ok_or("missing profile name")?;
+        options.profile_file = profile_file.map(PathBuf::from);
+        Ok(Some(options))
+    }
+
+    /// Override the settings saved in `profile` with those given as options.
+    fn apply(&self, profile: &mut Profile) -> Result<(), String> {
+        let endpoints = &mut profile.endpoints;
+        let settings = [
+            (&mut endpoints.request_token_url, &self.request_token_url),
+            (&mut endpoints.authorize_url, &self.authorize_url),
+            (&mut endpoints.access_token_url, &self.access_token_url),
+            (&mut endpoints.issuer, &self.issuer),
+            (
+                &mut endpoints.authorization_endpoint,
+                &self.authorization_endpoint,
+            ),
+            (&mut endpoints.token_endpoint, &self.token_endpoint),
+            (&mut profile.scope, &self.scope),
+        ];
+        for (saved, given) in settings {
+            if given.is_some() {
+                saved.clone_from(given);
+            }
+        }
+
+        if self.consumer_key.is_some() || self.consumer_secret.is_some() {
+            let saved = profile.consumer.take();
+            let key = self
+                .consumer_key
+                .clone()
+                .or_else(|| saved.as_ref().map(|c| c.key.clone()));
+            let secret = self
+                .consumer_secret
+                .clone()
+                .map(Secret::from)
+                .or_else(|| saved.map(|c| c.secret));
+            profile.consumer = Some(ConsumerCredentials {
+                key: key.ok_or("--consumer-secret requires --consumer-key")?,
+                secret: secret.ok_or("--consumer-key requires --consumer-secret")?,
+            });
+        }
+        if self.client_id.is_some() || self.client_secret.is_some() {
+            let saved = profile.client.take();
+            let client_id = self
+                .client_id
+                .clone()
+                .or_else(|| saved.as_ref().map(|c| c.client_id.clone()));
+            profile.client = Some(ClientCredentials {
+                client_id: client_id.ok_or("--client-secret requires --client-id")?,
+                client_secret: self
+                    .client_secret
+                    .clone()
+                    .map(Secret::from)
+                    .or_else(|| saved.and_then(|c| c.client_secret)),
+            });
+        }
+        Ok(())
+    }
+}
+
+pub fn main(args: &[String]) {
+    let options = match LoginOptions::parse(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("oauthsign login: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(&options) {
+        eprintln!("oauthsign login: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(options: &LoginOptions) -> Result<(), String> {
+    let path = crate::profile_file_path(options.profile_file.clone())
+        .ok_or("cannot locate the profile file")?;
+    let mut store = ProfileStore::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut profile = store.get(&options.profile).cloned().unwrap_or_default();
+    options.apply(&mut profile)?;
+
+    if profile.endpoints.request_token_url.is_some() {
+        flow::oauth1(&mut profile, options)?;
+    } else if profile.client.is_some() {
+        flow::oauth2(&mut profile, options)?;
+    } else {
+        return Err(format!(
+            "profile {} has neither a request token URL (OAuth 1) nor a client ID (OAuth 2)",
+            options.profile
+        ));
+    }
+
+    store.insert(options.profile.as_str(), profile);
+    store
+        .save()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    eprintln!("Saved credentials to profile {}", options.profile);
+    Ok(())
+}
+
+fn required<'p>(value: &'p Option<String>, option: &str) -> Result<&'p str, String> {
+    value
+        .as_deref()
+        .ok_or_else(|| format!("missing {}", option))
+}
+
+/// Show the authorization URL, opening it in the browser unless disabled.
+fn present(url: &str, no_browser: bool) {
+    eprintln!("Open this URL to authorize:\n\n    {}\n", url);
+    if !no_browser && !open_browser(url) {
+        eprintln!("(could not open a browser)");
+    }
+}
+
+fn open_browser(url: &str) -> bool {
+    #[cfg(target_os = "macos")]
+    let mut command = process::Command::new("open");
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = process::Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    };
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let mut command = process::Command::new("xdg-open");
+    command
+        .arg(url)
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(feature = "reqwest")]
+mod flow {
+    use super::{present, required, LoginOptions};
+    use oauthsign::blocking::{discover, request_token};
+    use oauthsign::credentials::{OAuth2Token, Profile, TokenCredentials};
+    use oauthsign::loopback::LoopbackListener;
+    use oauthsign::v1::{Secrets, SignedContent, Signer, TokenResponse, OAUTH_VALUE_CALLBACK_OOB};
+    use oauthsign::v2::{
+        AuthorizationServerMetadata, ClientAuthentication, MetadataKind, OAuthV2Client,
+        PkceVerifier,
+    };
+    use oauthsign::{OAuthSigner, RequestError, Secret};
+    use reqwest::blocking::Client;
+    use reqwest::header::AUTHORIZATION;
+    use std::borrow::Cow;
+    use std::io::{self, BufRead, Write};
+
+    pub fn oauth1(profile: &mut Profile, options: &LoginOptions) -> Result<(), String> {
+        let client = Client::new();
+        let consumer = profile
+            .consumer
+            .as_ref()
+            .ok_or("missing --consumer-key and --consumer-secret")?;
+        let endpoints = &profile.endpoints;
+        let request_token_url = required(&endpoints.request_token_url, "--request-token-url")?;
+        let authorize_url = required(&endpoints.authorize_url, "--authorize-url")?;
+        let access_token_url = required(&endpoints.access_token_url, "--access-token-url")?;
+
+        // temporary credentials
+        let listener = if options.pin {
+            None
+        } else {
+            Some(LoopbackListener::bind().map_err(|e| e.to_string())?)
+        };
+        let callback = listener
+            .as_ref()
+            .map_or(OAUTH_VALUE_CALLBACK_OOB, |l| l.redirect_uri());
+        let mut signer = Signer::<()>::new(consumer.key.as_str(), request_token_url, "POST");
+        signer.callback(callback);
+        let signed = signer
+            .sign(vec![], &consumer.secrets())
+            .map_err(|e| e.to_string())?;
+        let temporary = post_signed(&client, request_token_url, &signed)?;
+        let temporary_token = temporary.token.as_str();
+        let temporary_secret = temporary.token_secret.expose();
+
+        // resource owner authorization
+        let mut url = url::Url::parse(authorize_url).map_err(|e| e.to_string())?;
+        url.query_pairs_mut()
+            .append_pair("oauth_token", temporary_token);
+        present(url.as_str(), options.no_browser);
+        let verifier = match listener {
+            Some(listener) => listener
+                .wait_for_verifier(temporary_token)
+                .map_err(|e| e.to_string())?,
+            None => prompt("Enter the PIN: ")?,
+        };
+
+        // token credentials
+        let mut signer = Signer::<Cow<str>>::new(
+            consumer.key.as_str(),
+            access_token_url,
+            "POST",
+            temporary_token,
+        );
+        signer.verifier(verifier);
+        let signed = signer
+            .sign(
+                vec![],
+                &Secrets::<Secret>::new(consumer.secret.expose(), temporary_secret),
+            )
+            .map_err(|e| e.to_string())?;
+        let token = post_signed(&client, access_token_url, &signed)?;
+        for (key, value) in &token.extra {
+            if !key.starts_with("oauth_") {
+                eprintln!("{}: {}", key, value);
+            }
+        }
+        let token = TokenCredentials::new(token.token.as_str(), token.token_secret.expose());
+        profile.token = Some(token);
+        Ok(())
+    }
+
+    /// POST with the protocol parameters in the header, returning the credentials issued.
+    fn post_signed(
+        client: &Client,
+        endpoint: &str,
+        signed: &SignedContent,
+    ) -> Result<TokenResponse, String> {
+        let response = client
+            .post(endpoint)
+            .header(AUTHORIZATION, signed.authorization_header())
+            .send()
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        let body = response.text().map_err(|e| e.to_string())?;
+        if !status.is_success() {
+            return Err(format!("{}: {} {}", endpoint, status, body.trim()));
+        }
+        TokenResponse::from_slice(body.as_bytes()).ok_or_else(|| {
+            format!(
+                "{}: oauth_token or oauth_token_secret is missing in the response",
+                endpoint
+            )
+        })
+    }
+
+    fn prompt(message: &str) -> Result<String, String> {
+        eprint!("{}", message);
+        io::stderr().flush().map_err(|e| e.to_string())?;
+        let mut line = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .map_err(|e| e.to_string())?;
+        match line.trim() {
+            "" => Err("no input".to_string()),
+            input => Ok(input.to_string()),
+        }
+    }
+
+    pub fn oauth2(profile: &mut Profile, options: &LoginOptions) -> Result<(), String> {
+        let client = Client::new();
+        let metadata = match &profile.endpoints.issuer {
+            Some(issuer) => Some(discover_any(&client, issuer)?),
+            None => None,
+        };
+        if let Some(metadata) = &metadata {
+            let endpoints = &mut profile.endpoints;
+            if endpoints.authorization_endpoint.is_none() {
+                endpoints
+                    .authorization_endpoint
+                    .clone_from(&metadata.authorization_endpoint);
+            }
+            if endpoints.token_endpoint.is_none() {
+                endpoints
+                    .token_endpoint
+                    .clone_from(&metadata.token_endpoint);
+            }
+        }
+
+        let registration = profile.client.as_ref().ok_or("missing --client-id")?;
+        let client_id = registration.client_id.as_str();
+        let auth = match registration.client_secret.as_ref().map(Secret::expose) {
+            None => ClientAuthentication::none(client_id),
+            Some(secret) => {
+                let post = metadata.as_ref().is_some_and(|m| {
+                    !m.supports_token_endpoint_auth_method("client_secret_basic")
+                        && m.supports_token_endpoint_auth_method("client_secret_post")
+                });
+                if post {
+                    ClientAuthentication::client_secret_post(client_id, secret)
+                } else {
+                    ClientAuthentication::client_secret_basic(client_id, secret)
+                }
+            }
+        };
+        let endpoints = &profile.endpoints;
+        let mut oauth = OAuthV2Client::new(auth);
+        oauth
+            .authorization_endpoint(required(
+                &endpoints.authorization_endpoint,
+                "--authorization-endpoint",
+            )?)
+            .token_endpoint(required(&endpoints.token_endpoint, "--token-endpoint")?);
+
+        let listener = LoopbackListener::bind().map_err(|e| e.to_string())?;
+        let verifier = PkceVerifier::generate();
+        let state = uuid::Uuid::new_v4().to_simple().to_string();
+        let mut request = oauth
+            .authorization_request()
+            .ok_or("missing authorization endpoint")?;
+        request
+            .redirect_uri(listener.redirect_uri().to_string())
+            .state(state.as_str())
+            .pkce(&verifier);
+        if let Some(scope) = &profile.scope {
+            request.scope(scope.as_str());
+        }
+        let url = request.url().map_err(|e| e.to_string())?;
+        present(&url, options.no_browser);
+
+        let redirect_uri = listener.redirect_uri().to_string();
+        let code = listener.wait_for_code(&state).map_err(|e| e.to_string())?;
+        let request = oauth
+            .exchange_code(code, redirect_uri, Some(&verifier))
+            .ok_or("missing token endpoint")?;
+        let issued_at = chrono::Utc::now().timestamp();
+        let token = request_token(&client, &request).map_err(|e| e.to_string())?;
+
+        let token = OAuth2Token::from_response(&token, issued_at);
+        profile.oauth2_token = Some(token);
+        Ok(())
+    }
+
+    /// Fetch the metadata of `issuer`, trying RFC 8414 before OpenID Connect Discovery.
+    fn discover_any(client: &Client, issuer: &str) -> Result<AuthorizationServerMetadata, String> {
+        let mut last_error = None;
+        for kind in [MetadataKind::OAuth, MetadataKind::OpenId].iter() {
+            match discover(client, issuer, *kind) {
+                Ok(metadata) => return Ok(metadata),
+                // not published there, or not reachable.
+                Err(e @ RequestError::Status(_)) | Err(e @ RequestError::Http(_)) => {
+                    last_error = Some(e)
+                }
+                Err(e) => return Err(format!("{}: {}", issuer, e)),
+            }
+        }
+        Err(match last_error {
+            Some(e) => format!("{}: {}", issuer, e),
+            None => format!("{}: no metadata", issuer),
+        })
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+mod flow {
+    use super::LoginOptions;
+    use oauthsign::credentials::Profile;
+
+    const UNAVAILABLE: &str = "login is unavailable in builds without reqwest";
+
+    pub fn oauth1(_: &mut Profile, _: &LoginOptions) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn oauth2(_: &mut Profile, _: &LoginOptions) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = LoginOptions::parse(&args(
+            "github --client-id abc --scope repo --no-browser --profile-file creds.toml",
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(options.profile, "github");
+        assert_eq!(options.profile_file, Some(PathBuf::from("creds.toml")));
+        assert_eq!(options.client_id.as_deref(), Some("abc"));
+        assert_eq!(options.scope.as_deref(), Some("repo"));
+        assert!(options.no_browser);
+        assert!(!options.pin);
+
+        assert!(LoginOptions::parse(&args("--help")).unwrap().is_none());
+        assert!(LoginOptions::parse(&args("--pin")).is_err());
+        assert!(LoginOptions::parse(&args("a b")).is_err());
+        assert!(LoginOptions::parse(&args("a --client-id")).is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut profile = Profile {
+            consumer: Some(ConsumerCredentials::new("key", "secret")),
+            ..Profile::default()
+        };
+        profile.endpoints.authorize_url = Some("https://example.com/authorize".to_string());
+
+        let options = LoginOptions::parse(&args(
+            "tw -s rotated --request-token-url https://example.com/request",
+        ))
+        .unwrap()
+        .unwrap();
+        options.apply(&mut profile).unwrap();
+        assert_eq!(
+            profile.consumer,
+            Some(ConsumerCredentials::new("key", "rotated"))
+        );
+        assert_eq!(
+            profile.endpoints.request_token_url.as_deref(),
+            Some("https://example.com/request")
+        );
+        assert!(profile.endpoints.authorize_url.is_some());
+
+        let options = LoginOptions::parse(&args("gh --client-secret s"))
+            .unwrap()
+            .unwrap();
+        assert!(options.apply(&mut Profile::default()).is_err());
+    }
+}
@@ -0,0 +1,553 @@
+//! `oauthsign` signs OAuth 1.0a requests from the command line.
+//!
+//! It prints the `Authorization` header, the signature base string and the
+//! signature of a request (or the signed URL), and optionally sends it.
+//! `oauthsign login` obtains the credentials to sign with.
+// the login flows need reqwest.
+#[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+mod login;
+
+use oauthsign::credentials::{Profile, ProfileStore};
+use oauthsign::v1::{OAuthVersion, Secrets, SignatureMethod, SignedContent, Signer};
+use oauthsign::{OAuthParameter, OAuthSignedContent, OAuthSigner, Secret};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::{env, process};
+
+const USAGE: &str = "\
+Usage: oauthsign [OPTIONS] [METHOD] <URL>
+       oauthsign login [OPTIONS] <PROFILE>
+
+Options:
+  -X, --request <METHOD>            HTTP method (GET, or POST when -d/-F is given)
+  -d, --data <NAME=VALUE>           Form parameter
+  -F, --form <NAME=VALUE|NAME=@FILE>
+                                    Form parameter, read from FILE with `@`; with a
+                                    file, the parameters are sent as a multipart
+                                    body, which is not signed
+  -k, --consumer-key <KEY>          [env: OAUTHSIGN_CONSUMER_KEY]
+  -s, --consumer-secret <SECRET>    [env: OAUTHSIGN_CONSUMER_SECRET]
+  -t, --token <TOKEN>               [env: OAUTHSIGN_TOKEN]
+  -T, --token-secret <SECRET>       [env: OAUTHSIGN_TOKEN_SECRET]
+  -p, --profile <NAME>              Profile to read credentials from [env: OAUTHSIGN_PROFILE]
+      --profile-file <PATH>         [env: OAUTHSIGN_PROFILE_FILE, default: ~/.oauthsign.toml]
+      --realm <REALM>
+      --signature-method <METHOD>   HMAC-SHA1 (default), RSA-SHA1 or PLAINTEXT
+      --nonce <NONCE>
+      --timestamp <SECONDS>
+      --no-version                  Omit oauth_version
+  -o, --output <FORMAT>             all (default), header, base-string, signature or url
+      --send                        Send the signed request and print the response
+  -h, --help
+
+Run `oauthsign login --help` for obtaining credentials.
+
+Credentials given as options take precedence over the environment,
+which takes precedence over the profile file.
+
+The profile file is TOML (JSON when it ends with `.json`):
+
+  [default.consumer]
+  key = \"...\"
+  secret = \"...\"
+
+  [default.token]
+  token = \"...\"
+  secret = \"...\"
+";
+
+const ENV_CONSUMER_KEY: &str = "OAUTHSIGN_CONSUMER_KEY";
+const ENV_CONSUMER_SECRET: &str = "OAUTHSIGN_CONSUMER_SECRET";
+const ENV_TOKEN: &str = "OAUTHSIGN_TOKEN";
+const ENV_TOKEN_SECRET: &str = "OAUTHSIGN_TOKEN_SECRET";
+const ENV_PROFILE: &str = "OAUTHSIGN_PROFILE";
+const ENV_PROFILE_FILE: &str = "OAUTHSIGN_PROFILE_FILE";
+const DEFAULT_PROFILE: &str = "default";
+const DEFAULT_PROFILE_FILE: &str = ".oauthsign.toml";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    if args.first().map(String::as_str) == Some("login") {
+        return login::main(&args[1..]);
+    }
+    let options = match Options::parse(&args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("oauthsign: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(&options) {
+        eprintln!("oauthsign: {}", e);
+        process::exit(1);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Output {
+    All,
+    Header,
+    BaseString,
+    Signature,
+    Url,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FormValue {
+    Text(String),
+    File(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Credentials {
+    consumer_key: Option<String>,
+    consumer_secret: Option<Secret<'static>>,
+    token: Option<String>,
+    token_secret: Option<Secret<'static>>,
+}
+
+impl Credentials {
+    fn from_env() -> Self {
+        Credentials {
+            consumer_key: env::var(ENV_CONSUMER_KEY).ok(),
+            consumer_secret: env::var(ENV_CONSUMER_SECRET).ok().map(Secret::from),
+            token: env::var(ENV_TOKEN).ok(),
+            token_secret: env::var(ENV_TOKEN_SECRET).ok().map(Secret::from),
+        }
+    }
+
+    fn from_profile(profile: &Profile) -> Self {
+        let consumer = profile.consumer.as_ref();
+        let token = profile.token.as_ref();
+        Credentials {
+            consumer_key: consumer.map(|c| c.key.clone()),
+            consumer_secret: consumer.map(|c| c.secret.clone()),
+            token: token.map(|t| t.token.clone()),
+            token_secret: token.map(|t| t.secret.clone()),
+        }
+    }
+
+    /// Fill the missing values from `other`.
+    fn or(self, other: Credentials) -> Self {
+        Credentials {
+            consumer_key: self.consumer_key.or(other.consumer_key),
+            consumer_secret: self.consumer_secret.or(other.consumer_secret),
+            token: self.token.or(other.token),
+            token_secret: self.token_secret.or(other.token_secret),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    method: Option<String>,
+    url: String,
+    data: Vec<(String, String)>,
+    form: Vec<(String, FormValue)>,
+    credentials: Credentials,
+    profile: Option<String>,
+    profile_file: Option<PathBuf>,
+    realm: Option<String>,
+    signature_method: SignatureMethod,
+    nonce: Option<String>,
+    timestamp: Option<i64>,
+    no_version: bool,
+    output: Output,
+    send: bool,
+}
+
+impl Options {
+    /// Parse the command line, `None` when help is requested.
+    fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let mut positional = Vec::new();
+        let mut method = None;
+        let mut data = Vec::new();
+        let mut form = Vec::new();
+        let mut credentials = Credentials::default();
+        let mut profile = None;
+        let mut profile_file = None;
+        let mut realm = None;
+        let mut signature_method = SignatureMethod::HmacSha1;
+        let mut nonce = None;
+        let mut timestamp = None;
+        let mut no_version = false;
+        let mut output = Output::All;
+        let mut send = false;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{} requires a value", arg))
+            };
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-X" | "--request" => method = Some(value()?),
+                "-d" | "--data" => data.push(split_pair(&value()?)?),
+                "-F" | "--form" => {
+                    let (name, v) = split_pair(&value()?)?;
+                    let v = match v.strip_prefix('@') {
+                        Some(path) => FormValue::File(path.to_string()),
+                        None => FormValue::Text(v),
+                    };
+                    form.push((name, v));
+                }
+                "-k" | "--consumer-key" => credentials.consumer_key = Some(value()?),
+                "-s" | "--consumer-secret" => {
+                    credentials.consumer_secret = Some(Secret::from(value()?))
+                }
+                "-t" | "--token" => credentials.token = Some(value()?),
+                "-T" | "--token-secret" => credentials.token_secret = Some(Secret::from(value()?)),
+                "-p" | "--profile" => profile = Some(value()?),
+                "--profile-file" => profile_file = Some(PathBuf::from(value()?)),
+                "--realm" => realm = Some(value()?),
+                "--signature-method" => {
+                    signature_method = match value()?.to_ascii_uppercase().as_str() {
+                        "HMAC-SHA1" => SignatureMethod::HmacSha1,
+                        "PLAINTEXT" => SignatureMethod::PlainText,
+                        "RSA-SHA1" => SignatureMethod::RsaSha1,
+                        other => return Err(format!("unsupported signature method: {}", other)),
+                    }
+                }
+                "--nonce" => nonce = Some(value()?),
+                "--timestamp" => {
+                    let v = value()?;
+                    timestamp = Some(v.parse().map_err(|_| format!("invalid timestamp: {}", v))?)
+                }
+                "--no-version" => no_version = true,
+                "-o" | "--output" => {
+                    output = match value()?.as_str() {
+                        "all" => Output::All,
+                        "header" => Output::Header,
+                        "base-string" => Output::BaseString,
+                        "signature" => Output::Signature,
+                        "url" => Output::Url,
+                        other => return Err(format!("unknown output format: {}", other)),
+                    }
+                }
+                "--send" => send = true,
+                s if s.starts_with('-') && s.len() > 1 => {
+                    return Err(format!("unknown option: {}", s))
+                }
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        let url = match positional.len() {
+            1 => positional.remove(0),
+            2 => {
+                let url = positional.remove(1);
+                method = method.or_else(|| Some(positional.remove(0)));
+                url
+            }
+            0 => return Err("missing URL".to_string()),
+            _ => return Err(format!("unexpected argument: {}", positional[2])),
+        };
+        Ok(Some(Options {
+            method,
+            url,
+            data,
+            form,
+            credentials,
+            profile,
+            profile_file,
+            realm,
+            signature_method,
+            nonce,
+            timestamp,
+            no_version,
+            output,
+            send,
+        }))
+    }
+
+    fn http_method(&self) -> String {
+        match &self.method {
+            Some(method) => method.to_ascii_uppercase(),
+            None if self.data.is_empty() && self.form.is_empty() => "GET".to_string(),
+            None => "POST".to_string(),
+        }
+    }
+}
+
+fn split_pair(s: &str) -> Result<(String, String), String> {
+    let mut pair = s.splitn(2, '=');
+    match (pair.next(), pair.next()) {
+        (Some(name), Some(value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected NAME=VALUE: {}", s)),
+    }
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let credentials = resolve_credentials(options)?;
+    let consumer_key = credentials
+        .consumer_key
+        .as_deref()
+        .ok_or("missing consumer key")?;
+    let consumer_secret = credentials
+        .consumer_secret
+        .as_ref()
+        .ok_or("missing consumer secret")?
+        .expose();
+
+    let mut url = url::Url::parse(&options.url).map_err(|e| format!("{}: {}", options.url, e))?;
+    url.set_fragment(None);
+    let method = options.http_method();
+
+    // the query of the URL is signed, and so are the form parameters unless a
+    // file makes them a multipart/form-data body (RFC 5849 section 3.4.1.3.1).
+    let params = request_params(&options.data, &options.form);
+    let signed = match (&credentials.token, &credentials.token_secret) {
+        (Some(token), Some(token_secret)) => {
+            let mut signer = Signer::<Cow<str>>::new(
+                consumer_key,
+                url.as_str(),
+                method.as_str(),
+                token.as_str(),
+            );
+            configure(&mut signer, options);
+            signer.sign(
+                params,
+                &Secrets::<Secret>::new(consumer_secret, token_secret.expose()),
+            )
+        }
+        (None, None) => {
+            let mut signer = Signer::<()>::new(consumer_key, url.as_str(), method.as_str());
+            configure(&mut signer, options);
+            signer.sign(params, &Secrets::<()>::new(consumer_secret))
+        }
+        _ => return Err("token and token secret must be given together".to_string()),
+    }
+    .map_err(|e| e.to_string())?;
+
+    print_signed(&signed, &url, options.output);
+    if options.send {
+        let signed = OAuthSignedContent::from(signed);
+        send(url, &method, signed)?;
+    }
+    Ok(())
+}
+
+/// Parameters of the request besides the query, the form ones followed by the
+/// multipart ones.
+fn request_params<'a>(
+    data: &'a [(String, String)],
+    form: &'a [(String, FormValue)],
+) -> Vec<(Cow<'a, str>, OAuthParameter<'a>)> {
+    let form = form.iter().map(|(name, value)| {
+        let value = match value {
+            FormValue::Text(text) => OAuthParameter::from(text.as_str()),
+            FormValue::File(path) => OAuthParameter::from_file(path.as_str()),
+        };
+        (Cow::from(name.as_str()), value)
+    });
+    data.iter()
+        .map(|(name, value)| {
+            (
+                Cow::from(name.as_str()),
+                OAuthParameter::from(value.as_str()),
+            )
+        })
+        .chain(form)
+        .collect()
+}
+
+fn configure<T>(signer: &mut Signer<T>, options: &Options) {
+    signer.signature_method(options.signature_method);
+    if let Some(realm) = &options.realm {
+        signer.realm(realm.clone());
+    }
+    if let Some(nonce) = &options.nonce {
+        signer.nonce(nonce.clone());
+    }
+    if let Some(timestamp) = options.timestamp {
+        signer.timestamp(timestamp);
+    }
+    if options.no_version {
+        signer.version(OAuthVersion::None);
+    }
+}
+
+fn print_signed(signed: &SignedContent, url: &url::Url, output: Output) {
+    match output {
+        Output::All => {
+            println!("Authorization: {}", signed.authorization_header());
+            println!("Base string: {}", signed.base_string);
+            println!("Signature: {}", signed.signature);
+        }
+        Output::Header => println!("Authorization: {}", signed.authorization_header()),
+        Output::BaseString => println!("{}", signed.base_string),
+        Output::Signature => println!("{}", signed.signature),
+        Output::Url => {
+            let query = match url.query() {
+                Some(query) if !query.is_empty() => format!("{}&{}", query, signed.query_string()),
+                _ => signed.query_string(),
+            };
+            let mut url = url.clone();
+            url.set_query(Some(&query));
+            println!("{}", url);
+        }
+    }
+}
+
+fn resolve_credentials(options: &Options) -> Result<Credentials, String> {
+    let credentials = options.credentials.clone().or(Credentials::from_env());
+    let profile = options
+        .profile
+        .clone()
+        .or_else(|| env::var(ENV_PROFILE).ok());
+    let path = match profile_file_path(options.profile_file.clone()) {
+        Some(path) => path,
+        None if profile.is_some() => return Err("cannot locate the profile file".to_string()),
+        None => return Ok(credentials),
+    };
+    let from_profile = match &profile {
+        Some(name) => ProfileStore::open(&path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+            .get(name)
+            .map(Credentials::from_profile)
+            .ok_or_else(|| format!("{}: no profile named {}", path.display(), name))?,
+        // the default profile is optional.
+        None => ProfileStore::open(&path)
+            .ok()
+            .and_then(|store| store.get(DEFAULT_PROFILE).map(Credentials::from_profile))
+            .unwrap_or_default(),
+    };
+    Ok(credentials.or(from_profile))
+}
+
+/// The profile file given explicitly, in the environment, or in the home directory.
+fn profile_file_path(explicit: Option<PathBuf>) -> Option<PathBuf> {
+    explicit
+        .or_else(|| env::var_os(ENV_PROFILE_FILE).map(PathBuf::from))
+        .or_else(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| Path::new(&home).join(DEFAULT_PROFILE_FILE))
+        })
+}
+
+#[cfg(feature = "reqwest")]
+fn send(url: url::Url, method: &str, signed: OAuthSignedContent) -> Result<(), String> {
+    let http_method = reqwest::Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+    let client = reqwest::blocking::Client::new();
+    let mut request = client
+        .request(http_method, url)
+        .build()
+        .map_err(|e| e.to_string())?;
+    // file parts are streamed rather than read into memory.
+    signed.apply(&mut request).map_err(|e| e.to_string())?;
+
+    let mut response = client.execute(request).map_err(|e| e.to_string())?;
+    eprintln!("{:?} {}", response.version(), response.status());
+    response
+        .copy_to(&mut std::io::stdout())
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(not(feature = "reqwest"))]
+fn send(_: url::Url, _: &str, _: OAuthSignedContent) -> Result<(), String> {
+    Err("--send is unavailable in builds without reqwest".to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = Options::parse(&args(
+            "post https://api.example.com/update.json -d status=hello -F media=@cat.png -k ck",
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(options.http_method(), "POST");
+        assert_eq!(options.url, "https://api.example.com/update.json");
+        assert_eq!(
+            options.data,
+            vec![("status".to_string(), "hello".to_string())]
+        );
+        assert_eq!(
+            options.form,
+            vec![("media".to_string(), FormValue::File("cat.png".to_string()))]
+        );
+        assert_eq!(options.credentials.consumer_key.as_deref(), Some("ck"));
+
+        let options = Options::parse(&args("https://api.example.com/"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.http_method(), "GET");
+        let options = Options::parse(&args("-d a=1 https://api.example.com/"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.http_method(), "POST");
+
+        assert!(Options::parse(&args("-h")).unwrap().is_none());
+        assert!(Options::parse(&args("-d novalue https://api.example.com/")).is_err());
+        assert!(Options::parse(&args("--timestamp")).is_err());
+        assert!(Options::parse(&args("")).is_err());
+    }
+
+    #[test]
+    fn test_credentials_from_profile() {
+        let profile: Profile = toml::from_str(
+            "[consumer]\n\
+             key = \"xvz1evFS4wEEPTGEFPHBog\"\n\
+             secret = \"kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw\"\n",
+        )
+        .unwrap();
+        let merged = Credentials {
+            consumer_key: Some("override".to_string()),
+            token_secret: Some(Secret::from("secret")),
+            ..Credentials::default()
+        }
+        .or(Credentials::from_profile(&profile));
+        assert_eq!(merged.consumer_key.as_deref(), Some("override"));
+        assert!(merged.consumer_secret.is_some());
+        assert_eq!(merged.token_secret, Some(Secret::from("secret")));
+        assert_eq!(merged.token, None);
+    }
+
+    #[test]
+    fn test_request_params() {
+        let data = [("status".to_string(), "hi".to_string())];
+        let sign = |form: &[(String, FormValue)]| {
+            let mut signer =
+                Signer::<()>::new("ck", "https://api.example.com/update.json?a=1", "POST");
+            signer.nonce("nonce").timestamp(1);
+            let signed = signer
+                .sign(request_params(&data, form), &Secrets::<()>::new("cs"))
+                .unwrap();
+            (signed.base_string.clone(), OAuthSignedContent::from(signed))
+        };
+
+        let text = [("note".to_string(), FormValue::Text("x".to_string()))];
+        let (base_string, signed) = sign(&text);
+        assert!(base_string.ends_with("%26note%3Dx%26oauth_consumer_key%3Dck%26oauth_nonce%3Dnonce%26oauth_signature_method%3DHMAC-SHA1%26oauth_timestamp%3D1%26oauth_version%3D1.0%26status%3Dhi"));
+        let mut url = url::Url::parse("https://api.example.com/update.json?a=1").unwrap();
+        let body = signed.request_body("POST", &mut url).unwrap();
+        assert!(
+            matches!(body, Some(oauthsign::SignedBody::Form(body)) if body == "status=hi&note=x")
+        );
+
+        // a file makes every parameter a multipart part, so only the query is signed.
+        let file = [("media".to_string(), FormValue::File("cat.png".to_string()))];
+        let (base_string, signed) = sign(&file);
+        assert!(base_string.contains("a%3D1%26oauth_consumer_key"));
+        assert!(!base_string.contains("status"));
+        let mut url = url::Url::parse("https://api.example.com/update.json?a=1").unwrap();
+        let body = signed.request_body("POST", &mut url).unwrap();
+        assert!(matches!(body, Some(oauthsign::SignedBody::Multipart(_))));
+        assert_eq!(url.query(), Some("a=1"));
+    }
+}
@@ -16,6 +16,7 @@ where
     phantom_signed: PhantomData<TSigned>,
 }
 
+#[allow(dead_code)]
 impl<'a, TSigner, TSecret, TSigned> OAuthSignBuilder<'a, TSigner, TSecret, TSigned>
 where
     TSigner: OAuthSigner<'a, TSecret, TSigned>,
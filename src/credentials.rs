@@ -0,0 +1,191 @@
+//! Serializable credentials, shared by applications and the `oauthsign` CLI.
+mod store;
+
+pub use store::{ProfileStore, StoreError, StoreFormat};
+
+use crate::secret::Secret;
+use crate::v1::{self, Secrets};
+use crate::v2::{JwsKey, TokenResponse};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// OAuth 1 client credentials.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsumerCredentials {
+    pub key: String,
+    pub secret: Secret<'static>,
+}
+
+impl ConsumerCredentials {
+    pub fn new<TKey: Into<String>, TSecret: Into<String>>(key: TKey, secret: TSecret) -> Self {
+        ConsumerCredentials {
+            key: key.into(),
+            secret: Secret::from(secret.into()),
+        }
+    }
+
+    pub fn secrets(&self) -> Secrets<'_, ()> {
+        Secrets::<()>::new(self.secret.expose())
+    }
+
+    pub fn secrets_with<'a>(&'a self, token: &'a TokenCredentials) -> Secrets<'a, Secret<'a>> {
+        Secrets::<Secret>::new(self.secret.expose(), token.secret.expose())
+    }
+}
+
+/// OAuth 1 token credentials.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenCredentials {
+    pub token: String,
+    pub secret: Secret<'static>,
+}
+
+impl TokenCredentials {
+    pub fn new<TToken: Into<String>, TSecret: Into<String>>(
+        token: TToken,
+        secret: TSecret,
+    ) -> Self {
+        TokenCredentials {
+            token: token.into(),
+            secret: Secret::from(secret.into()),
+        }
+    }
+}
+
+impl From<v1::TokenResponse> for TokenCredentials {
+    fn from(response: v1::TokenResponse) -> Self {
+        TokenCredentials {
+            token: response.token,
+            secret: response.token_secret,
+        }
+    }
+}
+
+/// OAuth 2 client registration.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientCredentials {
+    pub client_id: String,
+    /// Absent for public clients.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<Secret<'static>>,
+}
+
+/// OAuth 2 tokens with the expiry of the access token.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OAuth2Token {
+    pub access_token: Secret<'static>,
+    pub token_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<Secret<'static>>,
+    /// UNIX time after which the access token is no longer valid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+impl OAuth2Token {
+    /// Tokens of a response received at `issued_at`.
+    pub fn from_response(response: &TokenResponse, issued_at: i64) -> Self {
+        OAuth2Token {
+            access_token: response.access_token.clone(),
+            token_type: response.token_type.clone(),
+            refresh_token: response.refresh_token.clone(),
+            expires_at: response.expires_at(issued_at),
+            scope: response.scope.clone(),
+        }
+    }
+
+    /// Tokens without an expiry never expire.
+    pub fn is_expired_at(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|e| e <= now)
+    }
+}
+
+/// Reference to a PKCS#8 PEM private key (RSA or EC P-256) kept outside of the store.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyReference {
+    pub path: PathBuf,
+    /// `kid` announced with signatures made by the key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+}
+
+impl KeyReference {
+    pub fn load(&self) -> io::Result<JwsKey> {
+        let pem = Secret::from(fs::read_to_string(&self.path)?);
+        JwsKey::from_pkcs8_pem(pem.expose()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a PKCS#8 PEM encoded RSA or P-256 private key",
+            )
+        })
+    }
+}
+
+/// Endpoints of the provider a profile belongs to.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Endpoints {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_token_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorize_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization_endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_endpoint: Option<String>,
+}
+
+impl Endpoints {
+    pub fn is_empty(&self) -> bool {
+        *self == Endpoints::default()
+    }
+}
+
+/// Credentials for one provider account.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    /// OAuth 2 scope to request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Endpoints::is_empty")]
+    pub endpoints: Endpoints,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consumer: Option<ConsumerCredentials>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<TokenCredentials>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<ClientCredentials>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth2_token: Option<OAuth2Token>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<KeyReference>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_oauth2_token() {
+        let response = TokenResponse::from_slice(
+            br#"{"access_token":"2YotnFZFEjr1zMsicMWpAA","token_type":"Bearer","expires_in":3600}"#,
+        )
+        .unwrap();
+        let token = OAuth2Token::from_response(&response, 1_000);
+        assert_eq!(token.expires_at, Some(4_600));
+        assert!(!token.is_expired_at(4_599));
+        assert!(token.is_expired_at(4_600));
+        assert!(!OAuth2Token {
+            expires_at: None,
+            ..token
+        }
+        .is_expired_at(i64::MAX));
+    }
+}
@@ -0,0 +1,252 @@
+use crate::credentials::Profile;
+use crate::secret::Secret;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// File format of a profile store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreFormat {
+    Toml,
+    Json,
+}
+
+impl StoreFormat {
+    /// JSON for `.json` files, TOML otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("json") => StoreFormat::Json,
+            _ => StoreFormat::Toml,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    TomlDe(toml::de::Error),
+    TomlSer(toml::ser::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "{}", e),
+            StoreError::TomlDe(e) => write!(f, "{}", e),
+            StoreError::TomlSer(e) => write!(f, "{}", e),
+            StoreError::Json(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> Self {
+        StoreError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for StoreError {
+    fn from(e: toml::de::Error) -> Self {
+        StoreError::TomlDe(e)
+    }
+}
+
+impl From<toml::ser::Error> for StoreError {
+    fn from(e: toml::ser::Error) -> Self {
+        StoreError::TomlSer(e)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self {
+        StoreError::Json(e)
+    }
+}
+
+/// Named profiles saved in a TOML or JSON file.
+///
+/// The file is written readable and writable by its owner only.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileStore {
+    path: PathBuf,
+    format: StoreFormat,
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl ProfileStore {
+    /// Open the store at `path`, empty when the file does not exist yet.
+    pub fn open<T: Into<PathBuf>>(path: T) -> Result<Self, StoreError> {
+        let path = path.into();
+        let format = StoreFormat::from_path(&path);
+        // the file holds secrets: its content is zeroed once parsed.
+        let profiles = match fs::read_to_string(&path).map(Secret::from) {
+            Ok(content) => match format {
+                StoreFormat::Toml => toml::from_str(content.expose())?,
+                StoreFormat::Json => serde_json::from_str(content.expose())?,
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(ProfileStore {
+            path,
+            format,
+            profiles,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> StoreFormat {
+        self.format
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+
+    /// The profile named `name`, added when missing.
+    pub fn entry(&mut self, name: &str) -> &mut Profile {
+        self.profiles.entry(name.to_string()).or_default()
+    }
+
+    pub fn insert<T: Into<String>>(&mut self, name: T, profile: Profile) -> Option<Profile> {
+        self.profiles.insert(name.into(), profile)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Profile> {
+        self.profiles.remove(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Write every profile back, replacing the file atomically.
+    pub fn save(&self) -> Result<(), StoreError> {
+        let content = Secret::from(match self.format {
+            StoreFormat::Toml => toml::to_string(&self.profiles)?,
+            StoreFormat::Json => serde_json::to_string_pretty(&self.profiles)?,
+        });
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            // the mode applies only to files created here.
+            if temporary.exists() {
+                fs::set_permissions(&temporary, fs::Permissions::from_mode(0o600))?;
+            }
+        }
+        let mut file = options.open(&temporary)?;
+        file.write_all(content.expose().as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::credentials::{
+        ClientCredentials, ConsumerCredentials, KeyReference, OAuth2Token, TokenCredentials,
+    };
+
+    fn profiles() -> Vec<(&'static str, Profile)> {
+        let mut twitter = Profile::default();
+        twitter.endpoints.request_token_url =
+            Some("https://api.twitter.com/oauth/request_token".to_string());
+        twitter.consumer = Some(ConsumerCredentials::new(
+            "xvz1evFS4wEEPTGEFPHBog",
+            "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw",
+        ));
+        twitter.token = Some(TokenCredentials::new(
+            "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb",
+            "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE",
+        ));
+        let server = Profile {
+            scope: Some("openid profile".to_string()),
+            client: Some(ClientCredentials {
+                client_id: "s6BhdRkqt3".to_string(),
+                client_secret: None,
+            }),
+            oauth2_token: Some(OAuth2Token {
+                access_token: Secret::from("2YotnFZFEjr1zMsicMWpAA"),
+                token_type: "Bearer".to_string(),
+                refresh_token: Some(Secret::from("tGzv3JOkF0XG5Qx2TlKWIA")),
+                expires_at: Some(1_318_622_958),
+                scope: None,
+            }),
+            private_key: Some(KeyReference {
+                path: PathBuf::from("/etc/keys/client.pem"),
+                key_id: Some("2020-01".to_string()),
+            }),
+            ..Profile::default()
+        };
+        vec![("twitter", twitter), ("server", server)]
+    }
+
+    fn round_trip(file_name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!(
+            "oauthsign-store-{}-{}",
+            std::process::id(),
+            file_name
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(file_name);
+        let mut store = ProfileStore::open(&path).unwrap();
+        assert_eq!(store.names().count(), 0);
+        for (name, profile) in profiles() {
+            store.insert(name, profile);
+        }
+        store.save().unwrap();
+
+        let reopened = ProfileStore::open(&path).unwrap();
+        assert_eq!(reopened, store);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        content
+    }
+
+    #[test]
+    fn test_toml_store() {
+        let content = round_trip("profiles.toml");
+        assert!(content.contains("[twitter.consumer]"));
+        assert!(content.contains("[server.oauth2_token]"));
+    }
+
+    #[test]
+    fn test_json_store() {
+        let content = round_trip("profiles.json");
+        assert!(content.trim_start().starts_with('{'));
+        assert!(content.contains("\"client_id\": \"s6BhdRkqt3\""));
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut store = ProfileStore::open("/nonexistent/profiles.toml").unwrap();
+        assert_eq!(store.format(), StoreFormat::Toml);
+        store.entry("a").scope = Some("read".to_string());
+        assert_eq!(store.get("a").unwrap().scope.as_deref(), Some("read"));
+        assert!(store.remove("a").is_some());
+        assert!(store.get("a").is_none());
+    }
+}
@@ -1,16 +1,43 @@
+//! OAuth 1 and OAuth 2 signing.
+//!
+//! Without the default `std` feature, only the `alloc` based OAuth 1 signing
+//! core (`v1::rfc5849`) is built.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod builder;
+#[cfg(feature = "std")]
+pub mod credentials;
+#[cfg(feature = "std")]
+pub mod loopback;
+#[cfg(feature = "std")]
+pub mod lti;
+#[cfg(feature = "std")]
+pub mod multipart;
+#[cfg(feature = "std")]
 pub mod parameters;
+pub mod secret;
 
 pub mod v1;
+#[cfg(feature = "std")]
+pub mod v1_old;
+#[cfg(feature = "std")]
 pub mod v2;
 
+#[cfg(feature = "std")]
 mod util;
 
+#[cfg(feature = "std")]
 pub use self::builder::OAuthSignBuilder;
+#[cfg(feature = "std")]
 pub use self::builder::OAuthSigner;
+#[cfg(feature = "std")]
 pub use self::parameters::*;
+pub use self::secret::Secret;
 
-#[cfg(not(feature = "without-reqwest"))]
+#[cfg(feature = "reqwest")]
 pub mod reqwest_bridge;
-#[cfg(not(feature = "without-reqwest"))]
+#[cfg(feature = "reqwest")]
 pub use self::reqwest_bridge::*;
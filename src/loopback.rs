@@ -0,0 +1,346 @@
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_CALLBACK_PATH: &str = "/callback";
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+const OAUTH2_PARAM_KEY_CODE: &str = "code";
+const OAUTH2_PARAM_KEY_STATE: &str = "state";
+const OAUTH2_PARAM_KEY_ERROR: &str = "error";
+const OAUTH2_PARAM_KEY_ERROR_DESCRIPTION: &str = "error_description";
+const OAUTH1_PARAM_KEY_TOKEN: &str = "oauth_token";
+const OAUTH1_PARAM_KEY_VERIFIER: &str = "oauth_verifier";
+const OAUTH1_PARAM_KEY_PROBLEM: &str = "oauth_problem";
+
+const SUCCESS_PAGE: &str = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Authorized</title></head>\
+<body><p>Authorization complete. You can close this window and return to the application.</p></body></html>";
+const FAILURE_PAGE: &str =
+    "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Authorization failed</title></head>\
+<body><p>Authorization failed. Please return to the application.</p></body></html>";
+
+#[derive(Debug)]
+pub enum LoopbackError {
+    Io(io::Error),
+    /// The callback request was not a valid HTTP request line.
+    Malformed,
+    /// `state` (OAuth 2) of the callback differs from the one sent.
+    StateMismatch,
+    /// `oauth_token` (OAuth 1) of the callback differs from the temporary credentials.
+    TokenMismatch,
+    MissingParameter(&'static str),
+    /// The user or the server denied the authorization.
+    Denied {
+        error: String,
+        description: Option<String>,
+    },
+}
+
+impl fmt::Display for LoopbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopbackError::Io(e) => write!(f, "{}", e),
+            LoopbackError::Malformed => write!(f, "malformed callback request"),
+            LoopbackError::StateMismatch => write!(f, "state does not match"),
+            LoopbackError::TokenMismatch => write!(f, "oauth_token does not match"),
+            LoopbackError::MissingParameter(key) => write!(f, "missing parameter: {}", key),
+            LoopbackError::Denied {
+                error,
+                description: Some(description),
+            } => write!(f, "{}: {}", error, description),
+            LoopbackError::Denied { error, .. } => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for LoopbackError {}
+
+impl From<io::Error> for LoopbackError {
+    fn from(e: io::Error) -> Self {
+        LoopbackError::Io(e)
+    }
+}
+
+/// Query parameters of the redirect, decoded.
+pub type CallbackParameters = Vec<(String, String)>;
+
+/// One-shot HTTP listener on `127.0.0.1` receiving the authorization redirect
+/// of a native application (RFC 8252 section 7.3).
+pub struct LoopbackListener {
+    listener: TcpListener,
+    path: String,
+    redirect_uri: String,
+    timeout: Option<Duration>,
+}
+
+impl LoopbackListener {
+    /// Bind an ephemeral port with the default `/callback` path.
+    pub fn bind() -> io::Result<Self> {
+        LoopbackListener::bind_with_path(DEFAULT_CALLBACK_PATH)
+    }
+
+    pub fn bind_with_path(path: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))?;
+        let path = format!("/{}", path.trim_start_matches('/'));
+        let redirect_uri = format!("http://127.0.0.1:{}{}", listener.local_addr()?.port(), path);
+        Ok(LoopbackListener {
+            listener,
+            path,
+            redirect_uri,
+            timeout: None,
+        })
+    }
+
+    /// Give up waiting for the callback after `timeout`.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// `redirect_uri` (OAuth 2) or `oauth_callback` (OAuth 1) to register with the request.
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    pub fn port(&self) -> u16 {
+        self.listener.local_addr().map(|a| a.port()).unwrap_or(0)
+    }
+
+    /// Wait for an OAuth 2 authorization code response, checking `state`.
+    pub fn wait_for_code(self, state: &str) -> Result<String, LoopbackError> {
+        self.wait_with(|params| {
+            if let Some(error) = find(params, OAUTH2_PARAM_KEY_ERROR) {
+                return Err(LoopbackError::Denied {
+                    error: error.to_string(),
+                    description: find(params, OAUTH2_PARAM_KEY_ERROR_DESCRIPTION)
+                        .map(|d| d.to_string()),
+                });
+            }
+            if find(params, OAUTH2_PARAM_KEY_STATE) != Some(state) {
+                return Err(LoopbackError::StateMismatch);
+            }
+            require(params, OAUTH2_PARAM_KEY_CODE)
+        })
+    }
+
+    /// Wait for an OAuth 1 authorization callback, checking the temporary `oauth_token`.
+    pub fn wait_for_verifier(self, oauth_token: &str) -> Result<String, LoopbackError> {
+        self.wait_with(|params| {
+            if let Some(problem) = find(params, OAUTH1_PARAM_KEY_PROBLEM) {
+                return Err(LoopbackError::Denied {
+                    error: problem.to_string(),
+                    description: None,
+                });
+            }
+            if find(params, OAUTH1_PARAM_KEY_TOKEN) != Some(oauth_token) {
+                return Err(LoopbackError::TokenMismatch);
+            }
+            require(params, OAUTH1_PARAM_KEY_VERIFIER)
+        })
+    }
+
+    /// Wait for the callback and return its raw parameters.
+    pub fn wait(self) -> Result<CallbackParameters, LoopbackError> {
+        self.wait_with(|params| Ok(params.clone()))
+    }
+
+    fn wait_with<T, F>(self, check: F) -> Result<T, LoopbackError>
+    where
+        F: Fn(&CallbackParameters) -> Result<T, LoopbackError>,
+    {
+        let deadline = self.timeout.map(|t| Instant::now() + t);
+        self.listener.set_nonblocking(deadline.is_some())?;
+        loop {
+            let mut stream = self.accept(deadline)?;
+            // browsers may ask for other resources (e.g. favicon) first.
+            let params = match read_request(&mut stream, &self.path)? {
+                Some(params) => params,
+                None => {
+                    respond(&mut stream, "404 Not Found", "")?;
+                    continue;
+                }
+            };
+            let result = check(&params);
+            let page = if result.is_ok() {
+                SUCCESS_PAGE
+            } else {
+                FAILURE_PAGE
+            };
+            respond(&mut stream, "200 OK", page)?;
+            return result;
+        }
+    }
+
+    fn accept(&self, deadline: Option<Instant>) -> io::Result<TcpStream> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(false)?;
+                    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                    return Ok(stream);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    if deadline.is_some_and(|d| Instant::now() >= d) {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "no authorization callback received",
+                        ));
+                    }
+                    thread::sleep(ACCEPT_POLL_INTERVAL);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Read the request head; returns the query parameters when `path` is requested.
+fn read_request(
+    stream: &mut TcpStream,
+    path: &str,
+) -> Result<Option<CallbackParameters>, LoopbackError> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // drain headers; the callback never carries a body.
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let target = match (parts.next(), parts.next()) {
+        (Some(_method), Some(target)) => target,
+        _ => return Err(LoopbackError::Malformed),
+    };
+    let mut target = target.splitn(2, '?');
+    if target.next() != Some(path) {
+        return Ok(None);
+    }
+    let params = url::form_urlencoded::parse(target.next().unwrap_or("").as_bytes())
+        .into_owned()
+        .collect();
+    Ok(Some(params))
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn find<'p>(params: &'p CallbackParameters, key: &str) -> Option<&'p str> {
+    params
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn require(params: &CallbackParameters, key: &'static str) -> Result<String, LoopbackError> {
+    find(params, key)
+        .map(|v| v.to_string())
+        .ok_or(LoopbackError::MissingParameter(key))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Read;
+
+    /// Simulate the browser following the redirect to `path_and_query`.
+    fn browse(port: u16, path_and_query: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: 127.0.0.1:{}\r\nAccept: text/html\r\n\r\n",
+            path_and_query, port
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn simulate_callback(port: u16, path_and_query: &str) -> thread::JoinHandle<String> {
+        let path_and_query = path_and_query.to_string();
+        thread::spawn(move || browse(port, &path_and_query))
+    }
+
+    #[test]
+    fn test_wait_for_code() {
+        let listener = LoopbackListener::bind().unwrap();
+        let port = listener.port();
+        assert_eq!(
+            listener.redirect_uri(),
+            format!("http://127.0.0.1:{}/callback", port)
+        );
+        let browser = thread::spawn(move || {
+            let favicon = browse(port, "/favicon.ico");
+            let callback = browse(port, "/callback?code=Splx%2FOzQ3d&state=af0ifjsldkj");
+            (favicon, callback)
+        });
+        let code = listener.wait_for_code("af0ifjsldkj").unwrap();
+        assert_eq!(code, "Splx/OzQ3d");
+        let (favicon, callback) = browser.join().unwrap();
+        assert!(favicon.starts_with("HTTP/1.1 404 Not Found"));
+        assert!(callback.starts_with("HTTP/1.1 200 OK"));
+        assert!(callback.contains("Authorization complete"));
+    }
+
+    #[test]
+    fn test_state_mismatch() {
+        let listener = LoopbackListener::bind().unwrap();
+        let browser = simulate_callback(listener.port(), "/callback?code=abc&state=forged");
+        match listener.wait_for_code("af0ifjsldkj") {
+            Err(LoopbackError::StateMismatch) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(browser.join().unwrap().contains("Authorization failed"));
+    }
+
+    #[test]
+    fn test_access_denied() {
+        let listener = LoopbackListener::bind().unwrap();
+        let _browser = simulate_callback(
+            listener.port(),
+            "/callback?error=access_denied&error_description=user+cancelled&state=s",
+        );
+        match listener.wait_for_code("s") {
+            Err(LoopbackError::Denied { error, description }) => {
+                assert_eq!(error, "access_denied");
+                assert_eq!(description.as_deref(), Some("user cancelled"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_wait_for_verifier() {
+        // https://tools.ietf.org/html/rfc5849#section-2.2
+        let listener = LoopbackListener::bind_with_path("ready").unwrap();
+        let _browser = simulate_callback(
+            listener.port(),
+            "/ready?oauth_token=hh5s93j4hdidpola&oauth_verifier=hfdp7dh39dks9884",
+        );
+        let verifier = listener.wait_for_verifier("hh5s93j4hdidpola").unwrap();
+        assert_eq!(verifier, "hfdp7dh39dks9884");
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut listener = LoopbackListener::bind().unwrap();
+        listener.timeout(Duration::from_millis(100));
+        match listener.wait() {
+            Err(LoopbackError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::TimedOut),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
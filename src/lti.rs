@@ -0,0 +1,457 @@
+//! IMS LTI 1.1 launches and Outcomes Service requests, signed with OAuth 1.0a.
+//!
+//! https://www.imsglobal.org/specs/ltiv1p1/implementation-guide
+use crate::builder::OAuthSigner;
+use crate::parameters::OAuthParameter;
+use crate::v1::{Clock, OAuthVersion, Secrets, SignedContent, Signer};
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+use url::form_urlencoded;
+
+const LTI_PARAM_KEY_MESSAGE_TYPE: &str = "lti_message_type";
+const LTI_PARAM_KEY_VERSION: &str = "lti_version";
+const LTI_PARAM_KEY_RESOURCE_LINK_ID: &str = "resource_link_id";
+pub const LTI_VALUE_MESSAGE_TYPE_BASIC_LAUNCH: &str = "basic-lti-launch-request";
+pub const LTI_VALUE_VERSION_1P0: &str = "LTI-1p0";
+/// Content type of Outcomes Service requests.
+pub const LTI_OUTCOMES_CONTENT_TYPE: &str = "application/xml";
+// launches have no callback, but OAuth 1.0a requires one.
+const LTI_VALUE_CALLBACK: &str = "about:blank";
+
+const OAUTH_PARAM_KEY_CONSUMER_KEY: &str = "oauth_consumer_key";
+const OAUTH_PARAM_KEY_NONCE: &str = "oauth_nonce";
+const OAUTH_PARAM_KEY_SIGNATURE: &str = "oauth_signature";
+const OAUTH_PARAM_KEY_SIGNATURE_METHOD: &str = "oauth_signature_method";
+const OAUTH_PARAM_KEY_TIMESTAMP: &str = "oauth_timestamp";
+const OAUTH_PARAM_KEY_VERSION: &str = "oauth_version";
+const OAUTH_VALUE_SIGMETHOD_HMACSHA1: &str = "HMAC-SHA1";
+/// Launches signed longer ago than this, in seconds, are rejected.
+pub const MAX_TIMESTAMP_SKEW: i64 = 300;
+
+#[derive(Debug)]
+pub enum LaunchError {
+    Io(io::Error),
+    Url(url::ParseError),
+    MissingParameter(&'static str),
+    /// LTI 1.1 launches are signed with HMAC-SHA1 only.
+    UnsupportedSignatureMethod(String),
+    /// `oauth_timestamp` is not a number or too far from the current time.
+    InvalidTimestamp,
+    InvalidSignature,
+}
+
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaunchError::Io(e) => write!(f, "{}", e),
+            LaunchError::Url(e) => write!(f, "{}", e),
+            LaunchError::MissingParameter(key) => write!(f, "missing parameter: {}", key),
+            LaunchError::UnsupportedSignatureMethod(method) => {
+                write!(f, "unsupported signature method: {}", method)
+            }
+            LaunchError::InvalidTimestamp => write!(f, "invalid or expired oauth_timestamp"),
+            LaunchError::InvalidSignature => write!(f, "invalid oauth_signature"),
+        }
+    }
+}
+
+impl std::error::Error for LaunchError {}
+
+impl From<io::Error> for LaunchError {
+    fn from(e: io::Error) -> Self {
+        LaunchError::Io(e)
+    }
+}
+
+impl From<url::ParseError> for LaunchError {
+    fn from(e: url::ParseError) -> Self {
+        LaunchError::Url(e)
+    }
+}
+
+/// Basic launch request of a tool consumer.
+pub struct LaunchRequest<'a> {
+    url: String,
+    query: Vec<(String, String)>,
+    params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    signer: Signer<'a, ()>,
+}
+
+impl<'a> LaunchRequest<'a> {
+    /// Launch of the tool at `url`; query parameters of `url` are signed too.
+    pub fn new<TConsumerKey, TResourceLinkId>(
+        url: &str,
+        consumer_key: TConsumerKey,
+        resource_link_id: TResourceLinkId,
+    ) -> Result<Self, url::ParseError>
+    where
+        TConsumerKey: Into<Cow<'a, str>>,
+        TResourceLinkId: Into<Cow<'a, str>>,
+    {
+        let mut endpoint = url::Url::parse(url)?;
+        let query = endpoint.query_pairs().into_owned().collect();
+        endpoint.set_query(None);
+        endpoint.set_fragment(None);
+        let mut signer = Signer::<()>::new(consumer_key, String::from(endpoint), "POST");
+        signer.callback(LTI_VALUE_CALLBACK);
+        Ok(LaunchRequest {
+            url: url.to_string(),
+            query,
+            params: vec![
+                (
+                    LTI_PARAM_KEY_MESSAGE_TYPE.into(),
+                    LTI_VALUE_MESSAGE_TYPE_BASIC_LAUNCH.into(),
+                ),
+                (LTI_PARAM_KEY_VERSION.into(), LTI_VALUE_VERSION_1P0.into()),
+                (
+                    LTI_PARAM_KEY_RESOURCE_LINK_ID.into(),
+                    resource_link_id.into(),
+                ),
+            ],
+            signer,
+        })
+    }
+
+    /// Launch parameter, e.g. `user_id`, `roles` or `custom_` ones.
+    pub fn param<TKey, TValue>(&mut self, key: TKey, value: TValue) -> &mut Self
+    where
+        TKey: Into<Cow<'a, str>>,
+        TValue: Into<Cow<'a, str>>,
+    {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Signer of the launch, to set its nonce generator or clock.
+    pub fn signer(&mut self) -> &mut Signer<'a, ()> {
+        &mut self.signer
+    }
+
+    pub fn sign(self, secrets: &Secrets<'a, ()>) -> io::Result<LaunchForm> {
+        let mut fields: Vec<(String, String)> = self
+            .params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let params = self
+            .query
+            .into_iter()
+            .map(|(k, v)| (Cow::from(k), OAuthParameter::StringValue(v.into())))
+            .chain(
+                self.params
+                    .into_iter()
+                    .map(|(k, v)| (k, OAuthParameter::StringValue(v))),
+            )
+            .collect();
+        let signed = self.signer.sign(params, secrets)?;
+        fields.extend(
+            signed
+                .payload
+                .iter()
+                .filter(|(k, _)| k.starts_with("oauth_"))
+                .map(|(k, v)| {
+                    (
+                        k.to_string(),
+                        percent_decode_str(v).decode_utf8_lossy().into(),
+                    )
+                }),
+        );
+        fields.push((OAUTH_PARAM_KEY_SIGNATURE.to_string(), signed.signature));
+        Ok(LaunchForm {
+            action: self.url,
+            fields,
+        })
+    }
+}
+
+/// Signed launch, posted by the user agent to the tool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LaunchForm {
+    pub action: String,
+    /// Launch and OAuth parameters, decoded.
+    pub fields: Vec<(String, String)>,
+}
+
+impl LaunchForm {
+    /// HTML page submitting the form as soon as it is loaded.
+    pub fn html(&self) -> String {
+        let inputs = self
+            .fields
+            .iter()
+            .map(|(k, v)| {
+                format!(
+                    "<input type=\"hidden\" name=\"{}\" value=\"{}\">",
+                    escape_markup(k),
+                    escape_markup(v)
+                )
+            })
+            .collect::<String>();
+        format!(
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Launch</title></head>\
+             <body onload=\"document.forms[0].submit()\">\
+             <form method=\"post\" action=\"{}\" enctype=\"application/x-www-form-urlencoded\">{}\
+             <noscript><button type=\"submit\">Continue</button></noscript></form></body></html>",
+            escape_markup(&self.action),
+            inputs
+        )
+    }
+}
+
+/// Launch received by a tool provider.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Launch {
+    params: Vec<(String, String)>,
+}
+
+impl Launch {
+    pub fn new(params: Vec<(String, String)>) -> Self {
+        Launch { params }
+    }
+
+    /// Parse an `application/x-www-form-urlencoded` launch body.
+    pub fn from_form(body: &[u8]) -> Self {
+        Launch::new(form_urlencoded::parse(body).into_owned().collect())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Key to look the consumer secret up with.
+    pub fn consumer_key(&self) -> Option<&str> {
+        self.get(OAUTH_PARAM_KEY_CONSUMER_KEY)
+    }
+
+    /// Nonce to check for replays, along with `oauth_timestamp`.
+    pub fn nonce(&self) -> Option<&str> {
+        self.get(OAUTH_PARAM_KEY_NONCE)
+    }
+
+    /// Check the signature and the timestamp of a launch posted to `url`.
+    ///
+    /// Replays within `MAX_TIMESTAMP_SKEW` are left to the caller to detect with `nonce`.
+    pub fn verify(
+        &self,
+        url: &str,
+        consumer_secret: &str,
+        clock: &dyn Clock,
+    ) -> Result<(), LaunchError> {
+        let required = |key: &'static str| self.get(key).ok_or(LaunchError::MissingParameter(key));
+        let consumer_key = required(OAUTH_PARAM_KEY_CONSUMER_KEY)?;
+        let signature = required(OAUTH_PARAM_KEY_SIGNATURE)?;
+        let nonce = required(OAUTH_PARAM_KEY_NONCE)?;
+        let signature_method = required(OAUTH_PARAM_KEY_SIGNATURE_METHOD)?;
+        if signature_method != OAUTH_VALUE_SIGMETHOD_HMACSHA1 {
+            return Err(LaunchError::UnsupportedSignatureMethod(
+                signature_method.to_string(),
+            ));
+        }
+        let timestamp = required(OAUTH_PARAM_KEY_TIMESTAMP)?
+            .parse::<i64>()
+            .map_err(|_| LaunchError::InvalidTimestamp)?;
+        if clock.now().abs_diff(timestamp) > MAX_TIMESTAMP_SKEW as u64 {
+            return Err(LaunchError::InvalidTimestamp);
+        }
+
+        let mut endpoint = url::Url::parse(url)?;
+        let query: Vec<(String, String)> = endpoint.query_pairs().into_owned().collect();
+        endpoint.set_query(None);
+        endpoint.set_fragment(None);
+        let mut signer = Signer::<()>::new(consumer_key, String::from(endpoint), "POST");
+        signer.nonce(nonce).timestamp(timestamp).version(
+            self.get(OAUTH_PARAM_KEY_VERSION)
+                .map_or(OAuthVersion::None, |v| OAuthVersion::Custom(v.into())),
+        );
+        // every other parameter, `oauth_callback` included, is signed as given.
+        let generated = [
+            OAUTH_PARAM_KEY_CONSUMER_KEY,
+            OAUTH_PARAM_KEY_NONCE,
+            OAUTH_PARAM_KEY_SIGNATURE,
+            OAUTH_PARAM_KEY_SIGNATURE_METHOD,
+            OAUTH_PARAM_KEY_TIMESTAMP,
+            OAUTH_PARAM_KEY_VERSION,
+        ];
+        let params = query
+            .iter()
+            .chain(
+                self.params
+                    .iter()
+                    .filter(|(k, _)| !generated.contains(&k.as_str())),
+            )
+            .map(|(k, v)| (Cow::from(k.as_str()), OAuthParameter::from(v.as_str())))
+            .collect();
+        let expected = signer.sign(params, &Secrets::<()>::new(consumer_secret))?;
+        if constant_time_eq(expected.signature.as_bytes(), signature.as_bytes()) {
+            Ok(())
+        } else {
+            Err(LaunchError::InvalidSignature)
+        }
+    }
+}
+
+/// Sign an Outcomes Service request, whose XML body is signed with `oauth_body_hash`.
+///
+/// The signer is built for `POST` to `lis_outcome_service_url`.
+pub fn sign_outcome_request<'a>(
+    mut signer: Signer<'a, ()>,
+    xml: &str,
+    secrets: &Secrets<'a, ()>,
+) -> io::Result<SignedContent<'a>> {
+    signer.body_hash(xml.as_bytes());
+    signer.sign(vec![], secrets)
+}
+
+/// Body of a `replaceResultRequest` setting the score of `sourced_id`,
+/// `None` unless the score is within 0.0 and 1.0.
+pub fn replace_result_xml(
+    message_identifier: &str,
+    sourced_id: &str,
+    score: f64,
+) -> Option<String> {
+    if !(0.0..=1.0).contains(&score) {
+        return None;
+    }
+    Some(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <imsx_POXEnvelopeRequest xmlns=\"http://www.imsglobal.org/services/ltiv1p1/xsd/imsoms_v1p0\">\
+         <imsx_POXHeader><imsx_POXRequestHeaderInfo>\
+         <imsx_version>V1.0</imsx_version>\
+         <imsx_messageIdentifier>{}</imsx_messageIdentifier>\
+         </imsx_POXRequestHeaderInfo></imsx_POXHeader>\
+         <imsx_POXBody><replaceResultRequest><resultRecord>\
+         <sourcedGUID><sourcedId>{}</sourcedId></sourcedGUID>\
+         <result><resultScore><language>en</language><textString>{}</textString></resultScore></result>\
+         </resultRecord></replaceResultRequest></imsx_POXBody>\
+         </imsx_POXEnvelopeRequest>",
+        escape_markup(message_identifier),
+        escape_markup(sourced_id),
+        score
+    ))
+}
+
+fn escape_markup(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::v1::FixedClock;
+
+    fn launch_form() -> LaunchForm {
+        let mut request = LaunchRequest::new(
+            "https://tool.example.com/launch?course=1",
+            "12345",
+            "120988f929-274612",
+        )
+        .unwrap();
+        request
+            .param("user_id", "292832126")
+            .param("roles", "Instructor")
+            .param("custom_note", "A & B <\"quoted\">");
+        request
+            .signer()
+            .nonce("c8350c0e47782d16d2fa48b2090c1d8f")
+            .timestamp(1_300_000_000);
+        request.sign(&Secrets::<()>::new("secret")).unwrap()
+    }
+
+    #[test]
+    fn test_launch_form() {
+        let form = launch_form();
+        assert_eq!(form.action, "https://tool.example.com/launch?course=1");
+        let launch = Launch::new(form.fields.clone());
+        assert_eq!(
+            launch.get("lti_message_type"),
+            Some("basic-lti-launch-request")
+        );
+        assert_eq!(launch.get("oauth_callback"), Some("about:blank"));
+        assert!(launch.get("course").is_none());
+
+        let html = form.html();
+        assert!(html.contains("action=\"https://tool.example.com/launch?course=1\""));
+        assert!(html.contains("value=\"A &amp; B &lt;&quot;quoted&quot;&gt;\""));
+        assert!(html.contains("document.forms[0].submit()"));
+    }
+
+    #[test]
+    fn test_verify_launch() {
+        let form = launch_form();
+        let url = "https://tool.example.com/launch?course=1";
+        let clock = FixedClock(1_300_000_100);
+        let body = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(form.fields.iter())
+            .finish();
+        let launch = Launch::from_form(body.as_bytes());
+        assert_eq!(launch.consumer_key(), Some("12345"));
+        assert!(launch.verify(url, "secret", &clock).is_ok());
+
+        assert!(matches!(
+            launch.verify(url, "wrong", &clock),
+            Err(LaunchError::InvalidSignature)
+        ));
+        assert!(matches!(
+            launch.verify("https://tool.example.com/launch?course=2", "secret", &clock),
+            Err(LaunchError::InvalidSignature)
+        ));
+        assert!(matches!(
+            launch.verify(url, "secret", &FixedClock(1_300_000_301)),
+            Err(LaunchError::InvalidTimestamp)
+        ));
+
+        let mut fields = form.fields;
+        fields.iter_mut().find(|(k, _)| k == "roles").unwrap().1 = "Administrator".to_string();
+        assert!(matches!(
+            Launch::new(fields.clone()).verify(url, "secret", &clock),
+            Err(LaunchError::InvalidSignature)
+        ));
+
+        // an extreme timestamp is rejected without overflowing.
+        fields
+            .iter_mut()
+            .find(|(k, _)| k == "oauth_timestamp")
+            .unwrap()
+            .1 = i64::MIN.to_string();
+        assert!(matches!(
+            Launch::new(fields).verify(url, "secret", &clock),
+            Err(LaunchError::InvalidTimestamp)
+        ));
+    }
+
+    #[test]
+    fn test_outcome_request() {
+        let xml = replace_result_xml("999999123", "3124567", 0.92).unwrap();
+        assert!(xml.contains("<sourcedId>3124567</sourcedId>"));
+        assert!(xml.contains("<textString>0.92</textString>"));
+        assert!(replace_result_xml("999999123", "3124567", 1.5).is_none());
+
+        // https://oauth.googlecode.com/svn/spec/ext/body_hash/1.0/oauth-bodyhash.html#anchor13
+        let signer = Signer::<()>::new("consumer", "https://lms.example.com/outcomes", "POST");
+        let signed =
+            sign_outcome_request(signer, "Hello World!", &Secrets::<()>::new("secret")).unwrap();
+        assert!(signed
+            .authorization_header()
+            .contains("oauth_body_hash=\"Lve95gjOVATpfV8EL5X4nxwjKHE%3D\""));
+        assert_eq!(signed.form_body(), "");
+    }
+}
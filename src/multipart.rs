@@ -0,0 +1,346 @@
+//! `multipart/form-data` bodies (RFC 7578) streamed from their sources.
+use std::borrow::{BorrowMut, Cow};
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Contents of a file part, read again from the start for every pass over the body.
+trait Source: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> Source for T {}
+
+enum Content<'a> {
+    Text(Cow<'a, str>),
+    Path(PathBuf),
+    Reader(Box<dyn Source + 'a>),
+}
+
+struct Part<'a> {
+    name: Cow<'a, str>,
+    filename: Option<Cow<'a, str>>,
+    content: Content<'a>,
+}
+
+/// `multipart/form-data` body whose file parts are never held in memory.
+///
+/// The body can be read more than once, e.g. to compute its `oauth_body_hash`
+/// before sending it.
+pub struct Multipart<'a> {
+    boundary: String,
+    parts: Vec<Part<'a>>,
+}
+
+impl<'a> Multipart<'a> {
+    pub fn new() -> Self {
+        Multipart::with_boundary(format!("oauthsign-{}", Uuid::new_v4().to_simple()))
+    }
+
+    pub fn with_boundary<T: Into<String>>(boundary: T) -> Self {
+        Multipart {
+            boundary: boundary.into(),
+            parts: Vec::new(),
+        }
+    }
+
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Value of the `Content-Type` header.
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    pub fn text<TName, TValue>(&mut self, name: TName, value: TValue) -> &mut Self
+    where
+        TName: Into<Cow<'a, str>>,
+        TValue: Into<Cow<'a, str>>,
+    {
+        self.push(name.into(), None, Content::Text(value.into()))
+    }
+
+    /// File part read from `path` when the body is read.
+    pub fn file<TName, TPath>(&mut self, name: TName, path: TPath) -> &mut Self
+    where
+        TName: Into<Cow<'a, str>>,
+        TPath: Into<PathBuf>,
+    {
+        let path = path.into();
+        let filename = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_string();
+        self.push(name.into(), Some(filename.into()), Content::Path(path))
+    }
+
+    /// File part read from `reader`, which is rewound for every pass over the body.
+    pub fn reader<TName, TFilename, R>(
+        &mut self,
+        name: TName,
+        filename: TFilename,
+        reader: R,
+    ) -> &mut Self
+    where
+        TName: Into<Cow<'a, str>>,
+        TFilename: Into<Cow<'a, str>>,
+        R: Read + Seek + Send + 'a,
+    {
+        self.push(
+            name.into(),
+            Some(filename.into()),
+            Content::Reader(Box::new(reader)),
+        )
+    }
+
+    fn push(
+        &mut self,
+        name: Cow<'a, str>,
+        filename: Option<Cow<'a, str>>,
+        content: Content<'a>,
+    ) -> &mut Self {
+        self.parts.push(Part {
+            name,
+            filename,
+            content,
+        });
+        self
+    }
+
+    /// Length of the body, looking up the size of every file part.
+    pub fn content_length(&mut self) -> io::Result<u64> {
+        let mut length = self.closing().len() as u64;
+        for i in 0..self.parts.len() {
+            length += self.head(i).len() as u64 + 2;
+            length += match &mut self.parts[i].content {
+                Content::Text(text) => text.len() as u64,
+                Content::Path(path) => fs::metadata(path)?.len(),
+                Content::Reader(reader) => {
+                    let end = reader.seek(SeekFrom::End(0))?;
+                    end - reader.seek(SeekFrom::Start(0))?
+                }
+            };
+        }
+        Ok(length)
+    }
+
+    /// Reader of the body which leaves `self` to be read again.
+    pub fn reader_by_ref(&mut self) -> MultipartReader<&mut Multipart<'a>> {
+        MultipartReader::new(self)
+    }
+
+    /// Reader of the body, e.g. to be sent as a streaming request body.
+    pub fn into_reader(self) -> MultipartReader<Multipart<'a>> {
+        MultipartReader::new(self)
+    }
+
+    /// Everything of part `i` that precedes its content.
+    fn head(&self, i: usize) -> Vec<u8> {
+        let part = &self.parts[i];
+        let mut head = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+            self.boundary,
+            escape(&part.name)
+        );
+        if let Some(filename) = &part.filename {
+            head.push_str(&format!(
+                "; filename=\"{}\"\r\nContent-Type: {}",
+                escape(filename),
+                OCTET_STREAM
+            ));
+        }
+        head.push_str("\r\n\r\n");
+        head.into_bytes()
+    }
+
+    fn closing(&self) -> Vec<u8> {
+        format!("--{}--\r\n", self.boundary).into_bytes()
+    }
+}
+
+impl Default for Multipart<'_> {
+    fn default() -> Self {
+        Multipart::new()
+    }
+}
+
+/// Quotes and line breaks can not appear in quoted names (RFC 7578 section 4.2).
+fn escape(name: &str) -> Cow<'_, str> {
+    if name.contains(&['"', '\r', '\n'][..]) {
+        Cow::Owned(
+            name.replace('"', "%22")
+                .replace('\r', "%0D")
+                .replace('\n', "%0A"),
+        )
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+enum State {
+    /// Delimiters and headers, or the contents of a text part.
+    Bytes(Cursor<Vec<u8>>),
+    File(File),
+    Reader,
+    Done,
+}
+
+/// Reader of a `Multipart` body, chunk by chunk from the sources of its parts.
+pub struct MultipartReader<M> {
+    multipart: M,
+    /// Index of the next part to be read.
+    next: usize,
+    /// Whether the contents of the current part come after the current `Bytes`.
+    content_pending: bool,
+    state: State,
+}
+
+impl<'a, M: BorrowMut<Multipart<'a>>> MultipartReader<M> {
+    fn new(multipart: M) -> Self {
+        MultipartReader {
+            multipart,
+            next: 0,
+            content_pending: false,
+            state: State::Bytes(Cursor::new(Vec::new())),
+        }
+    }
+
+    /// The body, to be read again.
+    pub fn into_inner(self) -> M {
+        self.multipart
+    }
+
+    fn advance(&mut self) -> io::Result<()> {
+        let multipart = self.multipart.borrow_mut();
+        if self.content_pending {
+            self.content_pending = false;
+            self.state = match &mut multipart.parts[self.next - 1].content {
+                Content::Path(path) => State::File(File::open(path)?),
+                Content::Reader(reader) => {
+                    reader.seek(SeekFrom::Start(0))?;
+                    State::Reader
+                }
+                Content::Text(_) => unreachable!("text is read along with the head"),
+            };
+            return Ok(());
+        }
+
+        let mut bytes = Vec::new();
+        if self.next > 0 {
+            bytes.extend(b"\r\n");
+        }
+        if self.next < multipart.parts.len() {
+            bytes.extend(multipart.head(self.next));
+            match &multipart.parts[self.next].content {
+                Content::Text(text) => bytes.extend(text.as_bytes()),
+                _ => self.content_pending = true,
+            }
+        } else if self.next == multipart.parts.len() {
+            bytes.extend(multipart.closing());
+        } else {
+            self.state = State::Done;
+            return Ok(());
+        }
+        self.next += 1;
+        self.state = State::Bytes(Cursor::new(bytes));
+        Ok(())
+    }
+}
+
+impl<'a, M: BorrowMut<Multipart<'a>>> Read for MultipartReader<M> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let n = match &mut self.state {
+                State::Bytes(bytes) => bytes.read(buf)?,
+                State::File(file) => file.read(buf)?,
+                State::Reader => {
+                    match &mut self.multipart.borrow_mut().parts[self.next - 1].content {
+                        Content::Reader(reader) => reader.read(buf)?,
+                        _ => unreachable!("only reader parts are read as such"),
+                    }
+                }
+                State::Done => return Ok(0),
+            };
+            if n > 0 {
+                return Ok(n);
+            }
+            self.advance()?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Reads one byte at a time, as a slow source would.
+    fn read_all<R: Read>(mut reader: R) -> Vec<u8> {
+        let mut body = Vec::new();
+        let mut buf = [0u8; 1];
+        while reader.read(&mut buf).unwrap() > 0 {
+            body.push(buf[0]);
+        }
+        body
+    }
+
+    #[test]
+    fn test_text_parts() {
+        let mut multipart = Multipart::with_boundary("b");
+        multipart.text("status", "hi").text("note", "x");
+        assert_eq!(
+            String::from_utf8(read_all(multipart.into_reader())).unwrap(),
+            "--b\r\nContent-Disposition: form-data; name=\"status\"\r\n\r\nhi\r\n\
+             --b\r\nContent-Disposition: form-data; name=\"note\"\r\n\r\nx\r\n--b--\r\n"
+        );
+        assert_eq!(
+            read_all(Multipart::with_boundary("b").into_reader()),
+            b"--b--\r\n"
+        );
+    }
+
+    #[test]
+    fn test_file_parts() {
+        let name = format!("oauthsign-{}-test-file-parts.bin", std::process::id());
+        let path = std::env::temp_dir().join(&name);
+        let contents = (0..20_000u32).map(|i| i as u8).collect::<Vec<_>>();
+        fs::write(&path, &contents).unwrap();
+
+        let mut multipart = Multipart::with_boundary("b");
+        multipart
+            .file("media", &path)
+            .reader("thumb", "a\"b.png", Cursor::new(b"png".to_vec()))
+            .text("status", "hi");
+        let length = multipart.content_length().unwrap();
+
+        let first = read_all(multipart.reader_by_ref());
+        let mut second = Vec::new();
+        multipart.into_reader().read_to_end(&mut second).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.len() as u64, length);
+
+        let head = format!(
+            "--b\r\nContent-Disposition: form-data; name=\"media\"; filename=\"{}\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n",
+            name
+        );
+        assert!(first.starts_with(head.as_bytes()));
+        assert_eq!(
+            &first[head.len()..head.len() + contents.len()],
+            &contents[..]
+        );
+        let rest = String::from_utf8(first[head.len() + contents.len()..].to_vec()).unwrap();
+        assert_eq!(
+            rest,
+            "\r\n--b\r\nContent-Disposition: form-data; name=\"thumb\"; filename=\"a%22b.png\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\npng\r\n\
+             --b\r\nContent-Disposition: form-data; name=\"status\"\r\n\r\nhi\r\n--b--\r\n"
+        );
+    }
+}
@@ -1,12 +1,51 @@
+mod ser;
+
+pub use ser::{to_parameters, to_parameters_with, ParameterError, Parameters, SequenceStyle};
+
+use crate::multipart::Multipart;
 use std::borrow::Cow;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
 
 pub enum OAuthParameter<'a> {
     StringValue(Cow<'a, str>),
     IntValue(i64),
+    UIntValue(u64),
+    /// Formatted as the shortest text which reads back as the same `f64`.
     FloatValue(f64),
+    /// `f64` formatted with a fixed number of digits after the decimal point.
+    DecimalValue(f64, usize),
+    BoolValue(bool, BoolFormat),
     FileValue(Cow<'a, str>),
     ByteValue(Cow<'a, [u8]>),
     NamedByteValue(Cow<'a, str>, Cow<'a, [u8]>),
+    /// Values sent under the same key, sorted by value when signed.
+    MultiValue(Vec<OAuthParameter<'a>>),
+}
+
+/// Text of `BoolValue`s.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoolFormat {
+    /// `true` or `false`
+    #[default]
+    TrueFalse,
+    /// `1` or `0`
+    OneZero,
+}
+
+impl BoolFormat {
+    pub fn format(self, value: bool) -> &'static str {
+        match (self, value) {
+            (BoolFormat::TrueFalse, true) => "true",
+            (BoolFormat::TrueFalse, false) => "false",
+            (BoolFormat::OneZero, true) => "1",
+            (BoolFormat::OneZero, false) => "0",
+        }
+    }
 }
 
 impl<'a> From<&'a str> for OAuthParameter<'a> {
@@ -26,6 +65,30 @@ impl From<i64> for OAuthParameter<'_> {
     }
 }
 
+impl From<i32> for OAuthParameter<'_> {
+    fn from(n: i32) -> Self {
+        OAuthParameter::IntValue(n.into())
+    }
+}
+
+impl From<u64> for OAuthParameter<'_> {
+    fn from(n: u64) -> Self {
+        OAuthParameter::UIntValue(n)
+    }
+}
+
+impl From<u32> for OAuthParameter<'_> {
+    fn from(n: u32) -> Self {
+        OAuthParameter::UIntValue(n.into())
+    }
+}
+
+impl From<bool> for OAuthParameter<'_> {
+    fn from(b: bool) -> Self {
+        OAuthParameter::BoolValue(b, BoolFormat::default())
+    }
+}
+
 impl From<f64> for OAuthParameter<'_> {
     fn from(n: f64) -> Self {
         OAuthParameter::FloatValue(n)
@@ -54,16 +117,332 @@ impl<'a> OAuthParameter<'a> {
     ) -> Self {
         OAuthParameter::NamedByteValue(name.into(), bytes.into())
     }
+
+    pub fn bool_as(value: bool, format: BoolFormat) -> Self {
+        OAuthParameter::BoolValue(value, format)
+    }
+
+    pub fn decimal(value: f64, places: usize) -> Self {
+        OAuthParameter::DecimalValue(value, places)
+    }
+
+    pub fn multi<I, T>(values: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OAuthParameter<'a>>,
+    {
+        OAuthParameter::MultiValue(values.into_iter().map(Into::into).collect())
+    }
+
+    /// Whether the value is sent as a file part of a `multipart/form-data` body.
+    pub(crate) fn is_part(&self) -> bool {
+        match self {
+            OAuthParameter::FileValue(_) | OAuthParameter::NamedByteValue(..) => true,
+            OAuthParameter::MultiValue(values) => values.iter().any(OAuthParameter::is_part),
+            _ => false,
+        }
+    }
+
+    /// Replace a `FileValue`, or those in a `MultiValue`, with a `NamedByteValue`
+    /// of the contents `read` returns, named after the file. Other values are
+    /// returned as is.
+    pub async fn load_file<F, Fut>(self, mut read: F) -> io::Result<OAuthParameter<'a>>
+    where
+        F: FnMut(PathBuf) -> Fut,
+        Fut: Future<Output = io::Result<Vec<u8>>>,
+    {
+        match self {
+            OAuthParameter::FileValue(path) => load(path, &mut read).await,
+            OAuthParameter::MultiValue(values) => {
+                let mut loaded = Vec::with_capacity(values.len());
+                for value in values {
+                    loaded.push(match value {
+                        OAuthParameter::FileValue(path) => load(path, &mut read).await?,
+                        value => value,
+                    });
+                }
+                Ok(OAuthParameter::MultiValue(loaded))
+            }
+            value => Ok(value),
+        }
+    }
+}
+
+async fn load<'a, F, Fut>(path: Cow<'a, str>, read: F) -> io::Result<OAuthParameter<'a>>
+where
+    F: FnOnce(PathBuf) -> Fut,
+    Fut: Future<Output = io::Result<Vec<u8>>>,
+{
+    let bytes = read(PathBuf::from(&*path)).await?;
+    Ok(OAuthParameter::NamedByteValue(
+        file_name(&path).to_string().into(),
+        bytes.into(),
+    ))
+}
+
+/// Parameters of a query or `application/x-www-form-urlencoded` body in their
+/// order, decoded as RFC 5849 section 3.4.1.3.1 requires: `+` is a space, and
+/// keys without `=` have an empty value.
+///
+/// Fails with `InvalidData` if a parameter does not decode to UTF-8.
+pub fn parse_query(query: &str) -> io::Result<Vec<(Cow<'_, str>, OAuthParameter<'_>)>> {
+    Ok(crate::util::destructure_query(query)?
+        .into_iter()
+        .map(|(k, v)| (k, OAuthParameter::StringValue(v)))
+        .collect())
+}
+
+/// Name of the file at `path`, sent along with its contents.
+pub(crate) fn file_name(path: &str) -> &str {
+    Path::new(path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
 }
 
+/// Parameter as it is sent.
+#[derive(Clone, Debug, PartialEq)]
 pub enum OAuthSignedParameter {
     StringValue(String),
+    /// Byte part: its file name and contents.
     ByteValue(String, Vec<u8>),
+    /// File part, read from its path as the body is sent.
+    FileValue(PathBuf),
 }
 
+impl OAuthSignedParameter {
+    /// Values of `p` as sent, one for each value of a `MultiValue`.
+    pub fn from_parameter(p: OAuthParameter<'_>) -> Vec<Self> {
+        let text = match p {
+            OAuthParameter::StringValue(s) => s.into_owned(),
+            OAuthParameter::IntValue(n) => n.to_string(),
+            OAuthParameter::UIntValue(n) => n.to_string(),
+            OAuthParameter::FloatValue(n) => n.to_string(),
+            OAuthParameter::DecimalValue(n, places) => format!("{:.*}", places, n),
+            OAuthParameter::BoolValue(b, format) => format.format(b).to_string(),
+            OAuthParameter::ByteValue(b) => base64::encode(&b),
+            OAuthParameter::NamedByteValue(n, b) => {
+                return vec![OAuthSignedParameter::ByteValue(
+                    n.into_owned(),
+                    b.into_owned(),
+                )]
+            }
+            OAuthParameter::FileValue(path) => {
+                return vec![OAuthSignedParameter::FileValue(PathBuf::from(&*path))]
+            }
+            OAuthParameter::MultiValue(values) => {
+                return values
+                    .into_iter()
+                    .flat_map(OAuthSignedParameter::from_parameter)
+                    .collect()
+            }
+        };
+        vec![OAuthSignedParameter::StringValue(text)]
+    }
+}
+
+/// Output of every signer, to be applied to an HTTP request.
+#[derive(Clone, Default, PartialEq)]
 pub struct OAuthSignedContent {
-    parameters: Vec<(String, OAuthSignedParameter)>,
-    signature: String,
+    /// Protocol parameters (e.g. `oauth_*` or `access_token`) decoded, which
+    /// the signer has already put in `headers` or `parameters`.
+    pub protocol_parameters: Vec<(String, String)>,
+    /// Headers to set, e.g. `Authorization` and `DPoP`.
+    pub headers: Vec<(&'static str, String)>,
+    /// Parameters to send in the query or the body.
+    pub parameters: Vec<(String, OAuthSignedParameter)>,
+    /// Parameters to send in the query whatever the method, e.g. those signed
+    /// along with a `multipart/form-data` body, which is not.
+    pub query: Vec<(String, String)>,
+    /// Signature of the request, for signers which sign one.
+    pub signature: Option<String>,
+    /// Boundary of the `multipart/form-data` body, e.g. that of the `Multipart`
+    /// whose `oauth_body_hash` was signed; a random one when `None`.
+    pub boundary: Option<String>,
 }
 
-impl OAuthSignedContent {}
+impl OAuthSignedContent {
+    /// Add parameters which are sent as they are, e.g. those of a DPoP request.
+    pub fn push_parameters<'a>(
+        &mut self,
+        params: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
+    ) -> &mut Self {
+        for (key, value) in params {
+            for value in OAuthSignedParameter::from_parameter(value) {
+                self.parameters.push((key.to_string(), value));
+            }
+        }
+        self
+    }
+
+    pub fn text_parameters(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.parameters.iter().filter_map(|(k, v)| match v {
+            OAuthSignedParameter::StringValue(v) => Some((k.as_str(), v.as_str())),
+            _ => None,
+        })
+    }
+
+    /// Key, file name and contents of the byte parts.
+    pub fn byte_parameters(&self) -> impl Iterator<Item = (&str, &str, &[u8])> {
+        self.parameters.iter().filter_map(|(k, v)| match v {
+            OAuthSignedParameter::ByteValue(name, bytes) => {
+                Some((k.as_str(), name.as_str(), bytes.as_slice()))
+            }
+            _ => None,
+        })
+    }
+
+    /// Whether the parameters have to be sent as a `multipart/form-data` body,
+    /// i.e. there are file or byte parts.
+    pub fn has_byte_parameters(&self) -> bool {
+        self.parameters
+            .iter()
+            .any(|(_, v)| !matches!(v, OAuthSignedParameter::StringValue(_)))
+    }
+
+    /// Text parameters as an `application/x-www-form-urlencoded` body or query.
+    pub fn form_body(&self) -> String {
+        url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(self.text_parameters())
+            .finish()
+    }
+
+    /// Every parameter as a `multipart/form-data` body, whose file parts are
+    /// read from their paths as it is read.
+    ///
+    /// The body is the same for the same `boundary`, so that the one sent can
+    /// be the one hashed before signing.
+    pub fn multipart(&self) -> Multipart<'static> {
+        let mut multipart = match &self.boundary {
+            Some(boundary) => Multipart::with_boundary(boundary.as_str()),
+            None => Multipart::new(),
+        };
+        for (key, value) in &self.parameters {
+            match value {
+                OAuthSignedParameter::StringValue(v) => {
+                    multipart.text(key.clone(), v.clone());
+                }
+                OAuthSignedParameter::ByteValue(name, bytes) => {
+                    multipart.reader(key.clone(), name.clone(), io::Cursor::new(bytes.clone()));
+                }
+                OAuthSignedParameter::FileValue(path) => {
+                    multipart.file(key.clone(), path.as_path());
+                }
+            }
+        }
+        multipart
+    }
+
+    /// Append the text parameters to the query of `url`.
+    ///
+    /// Fails if a protocol parameter is among them, e.g. the `access_token` of
+    /// `BearerMethod::FormBody`, since URLs end up in logs and histories.
+    pub fn apply_to_url(&self, url: &mut url::Url) -> io::Result<()> {
+        if self.parameters.is_empty() {
+            return Ok(());
+        }
+        if let Some((key, _)) = self
+            .parameters
+            .iter()
+            .find(|(k, _)| self.protocol_parameters.iter().any(|(p, _)| p == k))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} can not be sent in the query", key),
+            ));
+        }
+        url.query_pairs_mut().extend_pairs(self.text_parameters());
+        Ok(())
+    }
+
+    /// Body of a request of `http_method` to `url`, once `query` and the text
+    /// parameters of requests which carry them in the query are put in `url`.
+    pub fn request_body(
+        &self,
+        http_method: &str,
+        url: &mut url::Url,
+    ) -> io::Result<Option<SignedBody>> {
+        if !self.query.is_empty() {
+            url.query_pairs_mut()
+                .extend_pairs(self.query.iter().map(|(k, v)| (k, v)));
+        }
+        if self.parameters.is_empty() {
+            return Ok(None);
+        }
+        if OAuthSignedContent::sends_in_query(http_method) {
+            if self.has_byte_parameters() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "file and byte parameters can not be sent in the query",
+                ));
+            }
+            self.apply_to_url(url)?;
+            return Ok(None);
+        }
+        if !self.has_byte_parameters() {
+            return Ok(Some(SignedBody::Form(self.form_body())));
+        }
+        Ok(Some(SignedBody::Multipart(self.multipart())))
+    }
+
+    /// Apply the headers and parameters to `request`.
+    pub fn apply<R: ApplySigned>(&self, request: &mut R) -> Result<(), R::Error> {
+        request.apply_signed(self)
+    }
+
+    /// Whether requests of `http_method` carry the parameters in the query rather than the body.
+    pub fn sends_in_query(http_method: &str) -> bool {
+        ["GET", "HEAD", "DELETE"]
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(http_method))
+    }
+}
+
+impl fmt::Debug for OAuthSignedContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // values may be credentials, e.g. Bearer tokens; only the names are shown.
+        let protocol_parameters = self.protocol_parameters.iter().map(|(k, _)| k);
+        let headers = self.headers.iter().map(|(k, _)| k);
+        let parameters = self.parameters.iter().map(|(k, _)| k);
+        let query = self.query.iter().map(|(k, _)| k);
+        f.debug_struct("OAuthSignedContent")
+            .field(
+                "protocol_parameters",
+                &protocol_parameters.collect::<Vec<_>>(),
+            )
+            .field("headers", &headers.collect::<Vec<_>>())
+            .field("parameters", &parameters.collect::<Vec<_>>())
+            .field("query", &query.collect::<Vec<_>>())
+            .field("boundary", &self.boundary)
+            .finish()
+    }
+}
+
+/// Body of a request which an `OAuthSignedContent` is applied to.
+pub enum SignedBody {
+    /// `application/x-www-form-urlencoded` text parameters.
+    Form(String),
+    /// `multipart/form-data` parameters, to be streamed from their sources.
+    Multipart(Multipart<'static>),
+}
+
+impl SignedBody {
+    /// Value of the `Content-Type` header.
+    pub fn content_type(&self) -> String {
+        match self {
+            SignedBody::Form(_) => FORM_CONTENT_TYPE.to_string(),
+            SignedBody::Multipart(multipart) => multipart.content_type(),
+        }
+    }
+}
+
+/// HTTP request types which an `OAuthSignedContent` can be applied to.
+///
+/// The headers are set, and the parameters are sent in the query of `GET`,
+/// `HEAD` and `DELETE` requests or in the body of the others, as a
+/// `multipart/form-data` body if there are file or byte parts.
+pub trait ApplySigned {
+    type Error;
+
+    fn apply_signed(&mut self, signed: &OAuthSignedContent) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,837 @@
+use crate::parameters::OAuthParameter;
+use serde::ser::{self, Impossible, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+
+/// Parameters made by `to_parameters`, ready to be passed to a signer.
+pub type Parameters = Vec<(Cow<'static, str>, OAuthParameter<'static>)>;
+
+/// How the elements of a sequence are sent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SequenceStyle {
+    /// `id=1&id=2`
+    #[default]
+    Repeated,
+    /// `id=1%2C2`
+    CommaJoined,
+}
+
+#[derive(Debug)]
+pub enum ParameterError {
+    /// Value which has no representation as parameters.
+    Unsupported(&'static str),
+    Custom(String),
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::Unsupported(what) => write!(f, "unsupported as parameters: {}", what),
+            ParameterError::Custom(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+impl ser::Error for ParameterError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        ParameterError::Custom(message.to_string())
+    }
+}
+
+/// Flatten a struct or map into parameters, repeating the keys of sequences.
+///
+/// `None` fields are skipped, fields of nested structs and maps are flattened
+/// into the same list, and enums are sent as their variant name, or the
+/// content of the variant if it has any.
+pub fn to_parameters<T: Serialize + ?Sized>(value: &T) -> Result<Parameters, ParameterError> {
+    to_parameters_with(value, SequenceStyle::Repeated)
+}
+
+/// `to_parameters` with the elements of sequences sent as `sequences` says.
+pub fn to_parameters_with<T: Serialize + ?Sized>(
+    value: &T,
+    sequences: SequenceStyle,
+) -> Result<Parameters, ParameterError> {
+    let mut params = Vec::new();
+    value.serialize(Flattener {
+        params: &mut params,
+        sequences,
+        key: None,
+    })?;
+    Ok(params)
+}
+
+/// Text of a single element of a comma-joined sequence, or of a map key.
+fn to_text(value: OAuthParameter<'static>) -> Result<String, ParameterError> {
+    match value {
+        OAuthParameter::StringValue(s) => Ok(s.into_owned()),
+        OAuthParameter::IntValue(n) => Ok(n.to_string()),
+        OAuthParameter::UIntValue(n) => Ok(n.to_string()),
+        OAuthParameter::FloatValue(n) => Ok(n.to_string()),
+        OAuthParameter::DecimalValue(n, places) => Ok(format!("{:.*}", places, n)),
+        OAuthParameter::BoolValue(b, format) => Ok(format.format(b).to_string()),
+        _ => Err(ParameterError::Unsupported(
+            "bytes or multiple values in text",
+        )),
+    }
+}
+
+/// Serializer of the value holding the parameters: a struct, map or enum variant of those.
+struct Flattener<'p> {
+    params: &'p mut Parameters,
+    sequences: SequenceStyle,
+    /// Key of a map entry whose value is yet to come.
+    key: Option<Cow<'static, str>>,
+}
+
+impl<'p> Flattener<'p> {
+    fn field(&mut self, key: Cow<'static, str>) -> FieldSerializer<'_> {
+        FieldSerializer {
+            key,
+            params: self.params,
+            sequences: self.sequences,
+        }
+    }
+}
+
+macro_rules! unsupported {
+    ($($method:ident($($ty:ty),*) => $what:expr,)*) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<Self::Ok, Self::Error> {
+                Err(ParameterError::Unsupported($what))
+            }
+        )*
+    };
+}
+
+impl<'p> ser::Serializer for Flattener<'p> {
+    type Ok = ();
+    type Error = ParameterError;
+    type SerializeSeq = Impossible<(), ParameterError>;
+    type SerializeTuple = Impossible<(), ParameterError>;
+    type SerializeTupleStruct = Impossible<(), ParameterError>;
+    type SerializeTupleVariant = Impossible<(), ParameterError>;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    unsupported! {
+        serialize_bool(bool) => "bool without a key",
+        serialize_i8(i8) => "number without a key",
+        serialize_i16(i16) => "number without a key",
+        serialize_i32(i32) => "number without a key",
+        serialize_i64(i64) => "number without a key",
+        serialize_u8(u8) => "number without a key",
+        serialize_u16(u16) => "number without a key",
+        serialize_u32(u32) => "number without a key",
+        serialize_u64(u64) => "number without a key",
+        serialize_f32(f32) => "number without a key",
+        serialize_f64(f64) => "number without a key",
+        serialize_char(char) => "string without a key",
+        serialize_str(&str) => "string without a key",
+        serialize_bytes(&[u8]) => "bytes without a key",
+        serialize_unit_variant(&'static str, u32, &'static str) => "enum without a key",
+    }
+
+    fn serialize_none(self) -> Result<(), ParameterError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), ParameterError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), ParameterError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), ParameterError> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), ParameterError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), ParameterError> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, ParameterError> {
+        Err(ParameterError::Unsupported("sequence without a key"))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, ParameterError> {
+        Err(ParameterError::Unsupported("tuple without a key"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, ParameterError> {
+        Err(ParameterError::Unsupported("tuple without a key"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, ParameterError> {
+        Err(ParameterError::Unsupported("tuple without a key"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self, ParameterError> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, ParameterError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, ParameterError> {
+        Ok(self)
+    }
+}
+
+impl<'p> ser::SerializeStruct for Flattener<'p> {
+    type Ok = ();
+    type Error = ParameterError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ParameterError> {
+        value.serialize(self.field(Cow::Borrowed(key)))
+    }
+
+    fn end(self) -> Result<(), ParameterError> {
+        Ok(())
+    }
+}
+
+impl<'p> ser::SerializeStructVariant for Flattener<'p> {
+    type Ok = ();
+    type Error = ParameterError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ParameterError> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<(), ParameterError> {
+        Ok(())
+    }
+}
+
+impl<'p> ser::SerializeMap for Flattener<'p> {
+    type Ok = ();
+    type Error = ParameterError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ParameterError> {
+        let key = match key.serialize(ScalarSerializer)? {
+            Some(key) => to_text(key)?,
+            None => return Err(ParameterError::Unsupported("map key of None")),
+        };
+        self.key = Some(Cow::Owned(key));
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ParameterError> {
+        let key = self
+            .key
+            .take()
+            .ok_or(ParameterError::Unsupported("map value without a key"))?;
+        value.serialize(self.field(key))
+    }
+
+    fn end(self) -> Result<(), ParameterError> {
+        Ok(())
+    }
+}
+
+/// Serializer of the value of one field.
+struct FieldSerializer<'p> {
+    key: Cow<'static, str>,
+    params: &'p mut Parameters,
+    sequences: SequenceStyle,
+}
+
+impl<'p> FieldSerializer<'p> {
+    fn push(self, value: Option<OAuthParameter<'static>>) -> Result<(), ParameterError> {
+        if let Some(value) = value {
+            self.params.push((self.key, value));
+        }
+        Ok(())
+    }
+
+    fn flatten(self) -> Flattener<'p> {
+        Flattener {
+            params: self.params,
+            sequences: self.sequences,
+            key: None,
+        }
+    }
+
+    fn sequence(self) -> SequenceSerializer<'p> {
+        SequenceSerializer {
+            field: self,
+            values: Vec::new(),
+        }
+    }
+}
+
+macro_rules! scalar {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<(), ParameterError> {
+                let value = ScalarSerializer.$method(v)?;
+                self.push(value)
+            }
+        )*
+    };
+}
+
+impl<'p> ser::Serializer for FieldSerializer<'p> {
+    type Ok = ();
+    type Error = ParameterError;
+    type SerializeSeq = SequenceSerializer<'p>;
+    type SerializeTuple = SequenceSerializer<'p>;
+    type SerializeTupleStruct = SequenceSerializer<'p>;
+    type SerializeTupleVariant = SequenceSerializer<'p>;
+    type SerializeMap = Flattener<'p>;
+    type SerializeStruct = Flattener<'p>;
+    type SerializeStructVariant = Flattener<'p>;
+
+    scalar! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+    }
+
+    fn serialize_none(self) -> Result<(), ParameterError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), ParameterError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), ParameterError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), ParameterError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<(), ParameterError> {
+        let value = ScalarSerializer.serialize_unit_variant(name, index, variant)?;
+        self.push(value)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), ParameterError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), ParameterError> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<SequenceSerializer<'p>, ParameterError> {
+        Ok(self.sequence())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<SequenceSerializer<'p>, ParameterError> {
+        Ok(self.sequence())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<SequenceSerializer<'p>, ParameterError> {
+        Ok(self.sequence())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<SequenceSerializer<'p>, ParameterError> {
+        Ok(self.sequence())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Flattener<'p>, ParameterError> {
+        Ok(self.flatten())
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Flattener<'p>, ParameterError> {
+        Ok(self.flatten())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Flattener<'p>, ParameterError> {
+        Ok(self.flatten())
+    }
+}
+
+/// Serializer of the elements of a sequence field.
+struct SequenceSerializer<'p> {
+    field: FieldSerializer<'p>,
+    values: Vec<OAuthParameter<'static>>,
+}
+
+impl<'p> SequenceSerializer<'p> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ParameterError> {
+        if let Some(value) = value.serialize(ScalarSerializer)? {
+            self.values.push(value);
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), ParameterError> {
+        let FieldSerializer { key, params, .. } = self.field;
+        if self.values.is_empty() {
+            return Ok(());
+        }
+        match self.field.sequences {
+            SequenceStyle::Repeated => {
+                params.extend(self.values.into_iter().map(|v| (key.clone(), v)));
+            }
+            SequenceStyle::CommaJoined => {
+                let joined = self
+                    .values
+                    .into_iter()
+                    .map(to_text)
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join(",");
+                params.push((key, OAuthParameter::StringValue(joined.into())));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'p> ser::SerializeSeq for SequenceSerializer<'p> {
+    type Ok = ();
+    type Error = ParameterError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ParameterError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), ParameterError> {
+        self.finish()
+    }
+}
+
+impl<'p> ser::SerializeTuple for SequenceSerializer<'p> {
+    type Ok = ();
+    type Error = ParameterError;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), ParameterError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), ParameterError> {
+        self.finish()
+    }
+}
+
+impl<'p> ser::SerializeTupleStruct for SequenceSerializer<'p> {
+    type Ok = ();
+    type Error = ParameterError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ParameterError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), ParameterError> {
+        self.finish()
+    }
+}
+
+impl<'p> ser::SerializeTupleVariant for SequenceSerializer<'p> {
+    type Ok = ();
+    type Error = ParameterError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ParameterError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), ParameterError> {
+        self.finish()
+    }
+}
+
+/// Serializer of a single value, `None` if it is absent.
+struct ScalarSerializer;
+
+type Scalar = Option<OAuthParameter<'static>>;
+
+impl ser::Serializer for ScalarSerializer {
+    type Ok = Scalar;
+    type Error = ParameterError;
+    type SerializeSeq = Impossible<Scalar, ParameterError>;
+    type SerializeTuple = Impossible<Scalar, ParameterError>;
+    type SerializeTupleStruct = Impossible<Scalar, ParameterError>;
+    type SerializeTupleVariant = Impossible<Scalar, ParameterError>;
+    type SerializeMap = Impossible<Scalar, ParameterError>;
+    type SerializeStruct = Impossible<Scalar, ParameterError>;
+    type SerializeStructVariant = Impossible<Scalar, ParameterError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Scalar, ParameterError> {
+        Ok(Some(OAuthParameter::from(v)))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Scalar, ParameterError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Scalar, ParameterError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Scalar, ParameterError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Scalar, ParameterError> {
+        Ok(Some(OAuthParameter::IntValue(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Scalar, ParameterError> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Scalar, ParameterError> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Scalar, ParameterError> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Scalar, ParameterError> {
+        Ok(Some(OAuthParameter::UIntValue(v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Scalar, ParameterError> {
+        // widening to f64 would send the digits of the binary approximation.
+        Ok(Some(OAuthParameter::StringValue(v.to_string().into())))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Scalar, ParameterError> {
+        Ok(Some(OAuthParameter::FloatValue(v)))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Scalar, ParameterError> {
+        Ok(Some(OAuthParameter::StringValue(v.to_string().into())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Scalar, ParameterError> {
+        Ok(Some(OAuthParameter::StringValue(v.to_string().into())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Scalar, ParameterError> {
+        Ok(Some(OAuthParameter::ByteValue(v.to_vec().into())))
+    }
+
+    fn serialize_none(self) -> Result<Scalar, ParameterError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Scalar, ParameterError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Scalar, ParameterError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Scalar, ParameterError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Scalar, ParameterError> {
+        Ok(Some(OAuthParameter::StringValue(variant.into())))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Scalar, ParameterError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Scalar, ParameterError> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, ParameterError> {
+        Err(ParameterError::Unsupported("nested sequence"))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, ParameterError> {
+        Err(ParameterError::Unsupported("nested sequence"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, ParameterError> {
+        Err(ParameterError::Unsupported("nested sequence"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, ParameterError> {
+        Err(ParameterError::Unsupported("nested sequence"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, ParameterError> {
+        Err(ParameterError::Unsupported("map in a sequence or key"))
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, ParameterError> {
+        Err(ParameterError::Unsupported("struct in a sequence or key"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, ParameterError> {
+        Err(ParameterError::Unsupported("struct in a sequence or key"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::v1::{Secrets, Signer};
+    use crate::OAuthSigner;
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Visibility {
+        Public,
+        Unlisted,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Place {
+        Id(String),
+        Coordinates { lat: f64, long: f64 },
+    }
+
+    #[derive(Serialize)]
+    struct UpdateStatus {
+        status: &'static str,
+        in_reply_to_status_id: Option<u64>,
+        possibly_sensitive: bool,
+        media_ids: Vec<u64>,
+        visibility: Visibility,
+        place: Place,
+        #[serde(flatten)]
+        extra: BTreeMap<&'static str, &'static str>,
+    }
+
+    fn texts(params: Parameters) -> Vec<(String, String)> {
+        params
+            .into_iter()
+            .map(|(k, v)| (k.into_owned(), to_text(v).unwrap()))
+            .collect()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_to_parameters() {
+        let mut extra = BTreeMap::new();
+        extra.insert("trim_user", "1");
+        let request = UpdateStatus {
+            status: "Hello Ladies + Gentlemen, a signed OAuth request!",
+            in_reply_to_status_id: None,
+            possibly_sensitive: false,
+            media_ids: vec![1, 2],
+            visibility: Visibility::Unlisted,
+            place: Place::Coordinates {
+                lat: 37.5,
+                long: -122.25,
+            },
+            extra,
+        };
+        assert_eq!(
+            texts(to_parameters(&request).unwrap()),
+            pairs(&[
+                ("status", request.status),
+                ("possibly_sensitive", "false"),
+                ("media_ids", "1"),
+                ("media_ids", "2"),
+                ("visibility", "unlisted"),
+                ("lat", "37.5"),
+                ("long", "-122.25"),
+                ("trim_user", "1"),
+            ])
+        );
+
+        let request = UpdateStatus {
+            in_reply_to_status_id: Some(u64::MAX),
+            media_ids: vec![],
+            visibility: Visibility::Public,
+            place: Place::Id("df51dec6f4ee2b2c".to_string()),
+            extra: BTreeMap::new(),
+            ..request
+        };
+        assert_eq!(
+            texts(to_parameters_with(&request, SequenceStyle::CommaJoined).unwrap()),
+            pairs(&[
+                ("status", request.status),
+                ("in_reply_to_status_id", "18446744073709551615"),
+                ("possibly_sensitive", "false"),
+                ("visibility", "public"),
+                ("place", "df51dec6f4ee2b2c"),
+            ])
+        );
+        assert!(to_parameters(&"status").is_err());
+        assert!(to_parameters(
+            &[("ids", vec![vec![1]])]
+                .iter()
+                .cloned()
+                .collect::<BTreeMap<_, _>>()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sign_serialized() {
+        #[derive(Serialize)]
+        struct Photo {
+            file: &'static str,
+            size: &'static str,
+        }
+
+        let mut signer = Signer::<Cow<str>>::new(
+            "dpf43f3p2l4k3l03",
+            "http://photos.example.net/photos",
+            "GET",
+            "nnch734d00sl2jdk",
+        );
+        signer.nonce("chapoH").timestamp(137_131_202);
+        let secrets = Secrets::<crate::Secret>::new("kd94hf93k423kf44", "pfkkdhi9sl3r4s00");
+        let params = to_parameters(&Photo {
+            file: "vacation.jpg",
+            size: "original",
+        })
+        .unwrap();
+        assert_eq!(
+            signer.clone().sign(params, &secrets).unwrap().signature,
+            signer
+                .sign(
+                    vec![
+                        ("file".into(), "vacation.jpg".into()),
+                        ("size".into(), "original".into()),
+                    ],
+                    &secrets
+                )
+                .unwrap()
+                .signature
+        );
+    }
+}
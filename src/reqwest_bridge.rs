@@ -1 +1,564 @@
+use crate::builder::OAuthSigner;
+use crate::multipart::Multipart;
+use crate::parameters::{ApplySigned, OAuthParameter, OAuthSignedContent, SignedBody};
+use crate::v1::{OAuthV1Client, ProblemReport};
+use crate::v2::{
+    self, AuthorizationServerMetadata, DPoPKey, DPoPSigner, EndpointRequest, ErrorResponse,
+    IntrospectionResponse, JsonWebKeySet, MetadataKind, TokenResponse, DPOP_NONCE_HEADER,
+};
+use futures_util::stream::{self, TryStream};
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE, WWW_AUTHENTICATE,
+};
+use reqwest::{Body, Client, Method, Request, RequestBuilder, Response, StatusCode};
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{ErrorKind, Read};
+use std::sync::Mutex;
 
+const FORM_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+const MULTIPART_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub enum RequestError {
+    Io(std::io::Error),
+    InvalidHeaderName(reqwest::header::InvalidHeaderName),
+    InvalidHeader(reqwest::header::InvalidHeaderValue),
+    InvalidUrl(url::ParseError),
+    Http(reqwest::Error),
+    Json(serde_json::Error),
+    /// Error response returned by the authorization server.
+    Provider(ErrorResponse),
+    /// Problem reported by an OAuth 1 service, with the status of the response.
+    OAuthProblem(u16, ProblemReport),
+    /// Unsuccessful response without a parsable error body.
+    Status(u16),
+    /// Discovery document published for a different issuer.
+    IssuerMismatch(String),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Io(e) => write!(f, "{}", e),
+            RequestError::InvalidHeaderName(e) => write!(f, "{}", e),
+            RequestError::InvalidHeader(e) => write!(f, "{}", e),
+            RequestError::InvalidUrl(e) => write!(f, "{}", e),
+            RequestError::Http(e) => write!(f, "{}", e),
+            RequestError::Json(e) => write!(f, "{}", e),
+            RequestError::Provider(e) => write!(f, "error response: {}", e),
+            RequestError::OAuthProblem(status, report) => {
+                write!(f, "OAuth problem ({}): {}", status, report)
+            }
+            RequestError::Status(status) => write!(f, "unsuccessful response: {}", status),
+            RequestError::IssuerMismatch(issuer) => {
+                write!(f, "metadata published for another issuer: {}", issuer)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RequestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RequestError::Io(e) => Some(e),
+            RequestError::InvalidHeaderName(e) => Some(e),
+            RequestError::InvalidHeader(e) => Some(e),
+            RequestError::InvalidUrl(e) => Some(e),
+            RequestError::Http(e) => Some(e),
+            RequestError::Json(e) => Some(e),
+            RequestError::OAuthProblem(_, report) => Some(report),
+            RequestError::Provider(_)
+            | RequestError::Status(_)
+            | RequestError::IssuerMismatch(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RequestError {
+    fn from(e: std::io::Error) -> Self {
+        RequestError::Io(e)
+    }
+}
+
+impl From<reqwest::header::InvalidHeaderName> for RequestError {
+    fn from(e: reqwest::header::InvalidHeaderName) -> Self {
+        RequestError::InvalidHeaderName(e)
+    }
+}
+
+impl From<reqwest::header::InvalidHeaderValue> for RequestError {
+    fn from(e: reqwest::header::InvalidHeaderValue) -> Self {
+        RequestError::InvalidHeader(e)
+    }
+}
+
+impl From<url::ParseError> for RequestError {
+    fn from(e: url::ParseError) -> Self {
+        RequestError::InvalidUrl(e)
+    }
+}
+
+impl From<reqwest::Error> for RequestError {
+    fn from(e: reqwest::Error) -> Self {
+        RequestError::Http(e)
+    }
+}
+
+impl From<serde_json::Error> for RequestError {
+    fn from(e: serde_json::Error) -> Self {
+        RequestError::Json(e)
+    }
+}
+
+/// Set the headers of `signed` and its `Content-Type`, and return the body to send.
+fn apply_signed_parts(
+    signed: &OAuthSignedContent,
+    method: &reqwest::Method,
+    url: &mut url::Url,
+    headers: &mut HeaderMap,
+) -> Result<Option<SignedBody>, RequestError> {
+    for (name, value) in &signed.headers {
+        headers.insert(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(value)?,
+        );
+    }
+    let body = signed.request_body(method.as_str(), url)?;
+    if let Some(body) = &body {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str(&body.content_type())?);
+    }
+    Ok(body)
+}
+
+/// Chunks of `multipart`, each read on the blocking pool of the runtime.
+fn multipart_stream(
+    multipart: Multipart<'static>,
+) -> impl TryStream<Ok = Vec<u8>, Error = std::io::Error> + Send + Sync + 'static {
+    // the reader is moved to the blocking pool for every chunk; the mutex
+    // only makes the stream `Sync`, as reqwest requires.
+    stream::try_unfold(
+        Mutex::new(multipart.into_reader()),
+        |mut reader| async move {
+            let (reader, chunk) = tokio::task::spawn_blocking(move || {
+                let mut chunk = vec![0; MULTIPART_CHUNK_SIZE];
+                let read = reader
+                    .get_mut()
+                    .map_err(|_| ErrorKind::Other)?
+                    .read(&mut chunk)?;
+                chunk.truncate(read);
+                Ok::<_, std::io::Error>((reader, chunk))
+            })
+            .await
+            .map_err(std::io::Error::other)??;
+            Ok(if chunk.is_empty() {
+                None
+            } else {
+                Some((chunk, reader))
+            })
+        },
+    )
+}
+
+impl ApplySigned for Request {
+    type Error = RequestError;
+
+    /// Multipart bodies are streamed; their file parts are read on the blocking
+    /// pool of the runtime, so the request has to be sent within it.
+    fn apply_signed(&mut self, signed: &OAuthSignedContent) -> Result<(), RequestError> {
+        let method = self.method().clone();
+        let mut url = self.url().clone();
+        let body = apply_signed_parts(signed, &method, &mut url, self.headers_mut())?;
+        *self.url_mut() = url;
+        match body {
+            Some(SignedBody::Form(body)) => *self.body_mut() = Some(body.into()),
+            Some(SignedBody::Multipart(mut multipart)) => {
+                let length = multipart.content_length()?;
+                self.headers_mut().insert(CONTENT_LENGTH, length.into());
+                *self.body_mut() = Some(Body::wrap_stream(multipart_stream(multipart)));
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+impl ApplySigned for reqwest::blocking::Request {
+    type Error = RequestError;
+
+    /// Multipart bodies are streamed from the sources of their parts.
+    fn apply_signed(&mut self, signed: &OAuthSignedContent) -> Result<(), RequestError> {
+        let method = self.method().clone();
+        let mut url = self.url().clone();
+        let body = apply_signed_parts(signed, &method, &mut url, self.headers_mut())?;
+        *self.url_mut() = url;
+        match body {
+            Some(SignedBody::Form(body)) => *self.body_mut() = Some(body.into()),
+            Some(SignedBody::Multipart(mut multipart)) => {
+                let length = multipart.content_length()?;
+                *self.body_mut() = Some(reqwest::blocking::Body::sized(
+                    multipart.into_reader(),
+                    length,
+                ));
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+impl OAuthV1Client {
+    /// Send a signed `method` request to `url` with `params`, in the query
+    /// or the body depending on `method`.
+    ///
+    /// Unsuccessful responses are `RequestError::OAuthProblem` when the service
+    /// reports the problem, `RequestError::Status` otherwise.
+    pub async fn request<'a>(
+        &'a self,
+        client: &Client,
+        method: Method,
+        url: &str,
+        params: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
+    ) -> Result<Response, RequestError> {
+        let url = url::Url::parse(url)?;
+        let signed = self.sign(method.as_str(), &url, params)?;
+        let mut request = Request::new(method, url);
+        signed.apply(&mut request)?;
+        let response = client.execute(request).await?;
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(oauth1_error(response).await)
+        }
+    }
+
+    pub async fn get<'a>(
+        &'a self,
+        client: &Client,
+        url: &str,
+        params: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
+    ) -> Result<Response, RequestError> {
+        self.request(client, Method::GET, url, params).await
+    }
+
+    pub async fn post<'a>(
+        &'a self,
+        client: &Client,
+        url: &str,
+        params: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
+    ) -> Result<Response, RequestError> {
+        self.request(client, Method::POST, url, params).await
+    }
+}
+
+/// The problem reported in the `WWW-Authenticate` header or the body of `response`.
+async fn oauth1_error(response: Response) -> RequestError {
+    let status = response.status().as_u16();
+    let challenge = response
+        .headers()
+        .get_all(WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find_map(ProblemReport::from_www_authenticate);
+    if let Some(report) = challenge {
+        return RequestError::OAuthProblem(status, report);
+    }
+    match response.bytes().await {
+        Ok(body) => ProblemReport::from_slice(&body)
+            .map(|report| RequestError::OAuthProblem(status, report))
+            .unwrap_or(RequestError::Status(status)),
+        Err(e) => RequestError::Http(e),
+    }
+}
+
+/// Attach a DPoP proof (and the DPoP-bound `access_token`) to `request`,
+/// applied like any other `OAuthSignedContent`.
+pub fn apply_dpop(
+    request: &mut Request,
+    key: &DPoPKey,
+    access_token: Option<&str>,
+    nonce: Option<&str>,
+) -> Result<(), RequestError> {
+    let mut signer = DPoPSigner::new(request.url().to_string(), request.method().to_string());
+    if let Some(token) = access_token {
+        signer.access_token(token);
+    }
+    if let Some(nonce) = nonce {
+        signer.nonce(nonce);
+    }
+    let signed = OAuthSignedContent::from(signer.sign(Vec::new(), key)?);
+    signed.apply(request)
+}
+
+/// Send `request` with a DPoP proof, retrying once when the server demands a nonce.
+///
+/// Requests with streaming bodies can not be replayed and are sent only once.
+/// A 400 response with a `DPoP-Nonce` is buffered to read its error, and
+/// returned without its `url` when it is not retried.
+/// The `DPoP-Nonce` of the returned response should be passed to the next call.
+pub async fn execute_with_dpop(
+    client: &Client,
+    request: Request,
+    key: &DPoPKey,
+    access_token: Option<&str>,
+    nonce: Option<&str>,
+) -> Result<Response, RequestError> {
+    let retry = request.try_clone();
+    let mut request = request;
+    apply_dpop(&mut request, key, access_token, nonce)?;
+    let response = client.execute(request).await?;
+
+    // authorization servers report use_dpop_nonce in the body of a 400.
+    let (response, body) = if response.status() == StatusCode::BAD_REQUEST
+        && response.headers().contains_key(DPOP_NONCE_HEADER)
+        && retry.is_some()
+    {
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        let mut buffered = http::Response::new(body.clone());
+        *buffered.status_mut() = status;
+        *buffered.version_mut() = version;
+        *buffered.headers_mut() = headers;
+        (Response::from(buffered), body)
+    } else {
+        (response, Default::default())
+    };
+
+    let challenge = {
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+        };
+        v2::nonce_challenge(
+            response.status().as_u16(),
+            header(WWW_AUTHENTICATE.as_str()),
+            &body,
+            header(DPOP_NONCE_HEADER),
+        )
+        .map(|n| n.to_string())
+    };
+    match (challenge, retry) {
+        (Some(nonce), Some(mut retry)) => {
+            apply_dpop(&mut retry, key, access_token, Some(&nonce))?;
+            Ok(client.execute(retry).await?)
+        }
+        _ => Ok(response),
+    }
+}
+
+/// Build a form POST to an authorization server endpoint.
+pub fn endpoint_request(client: &Client, request: &EndpointRequest) -> RequestBuilder {
+    request.headers.iter().fold(
+        client
+            .post(request.endpoint.as_ref())
+            .header(CONTENT_TYPE, FORM_CONTENT_TYPE)
+            .body(request.body()),
+        |builder, (name, value)| builder.header(*name, value.as_str()),
+    )
+}
+
+/// Fetch and validate the metadata document of `issuer`.
+pub async fn discover(
+    client: &Client,
+    issuer: &str,
+    kind: MetadataKind,
+) -> Result<AuthorizationServerMetadata, RequestError> {
+    let response = client.get(&kind.discovery_url(issuer)?).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(RequestError::Status(status.as_u16()));
+    }
+    issued_metadata(issuer, &response.bytes().await?)
+}
+
+/// Metadata document `body`, unless it is published for another issuer than `issuer`.
+fn issued_metadata(issuer: &str, body: &[u8]) -> Result<AuthorizationServerMetadata, RequestError> {
+    let metadata = AuthorizationServerMetadata::from_slice(body)?;
+    if metadata.is_issued_by(issuer) {
+        Ok(metadata)
+    } else {
+        Err(RequestError::IssuerMismatch(metadata.issuer))
+    }
+}
+
+/// Fetch the JWK Set published at `jwks_uri`.
+pub async fn fetch_jwks(client: &Client, jwks_uri: &str) -> Result<JsonWebKeySet, RequestError> {
+    let response = client.get(jwks_uri).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(RequestError::Status(status.as_u16()));
+    }
+    Ok(JsonWebKeySet::from_slice(&response.bytes().await?)?)
+}
+
+/// Send an access token request to the token endpoint.
+pub async fn request_token(
+    client: &Client,
+    request: &EndpointRequest<'_>,
+) -> Result<TokenResponse, RequestError> {
+    let body = send_endpoint_request(client, request).await?;
+    Ok(TokenResponse::from_slice(&body)?)
+}
+
+/// Send a token introspection request (RFC 7662).
+pub async fn introspect(
+    client: &Client,
+    request: &EndpointRequest<'_>,
+) -> Result<IntrospectionResponse, RequestError> {
+    let body = send_endpoint_request(client, request).await?;
+    Ok(IntrospectionResponse::from_slice(&body)?)
+}
+
+/// Send a token revocation request (RFC 7009).
+pub async fn revoke(client: &Client, request: &EndpointRequest<'_>) -> Result<(), RequestError> {
+    send_endpoint_request(client, request).await.map(|_| ())
+}
+
+async fn send_endpoint_request(
+    client: &Client,
+    request: &EndpointRequest<'_>,
+) -> Result<Vec<u8>, RequestError> {
+    let response = endpoint_request(client, request).send().await?;
+    let status = response.status();
+    endpoint_response(status, response.bytes().await?.to_vec())
+}
+
+/// `body` of a successful response, or the error the authorization server returned.
+fn endpoint_response(status: StatusCode, body: Vec<u8>) -> Result<Vec<u8>, RequestError> {
+    if status.is_success() {
+        Ok(body)
+    } else {
+        Err(ErrorResponse::from_slice(&body)
+            .map(RequestError::Provider)
+            .unwrap_or_else(|_| RequestError::Status(status.as_u16())))
+    }
+}
+
+/// Requests to authorization servers with the blocking client of reqwest.
+pub mod blocking {
+    use super::{endpoint_response, issued_metadata, RequestError, FORM_CONTENT_TYPE};
+    use crate::v2::{AuthorizationServerMetadata, EndpointRequest, MetadataKind, TokenResponse};
+    use reqwest::blocking::{Client, RequestBuilder};
+    use reqwest::header::CONTENT_TYPE;
+
+    /// Build a form POST to an authorization server endpoint.
+    pub fn endpoint_request(client: &Client, request: &EndpointRequest) -> RequestBuilder {
+        request.headers.iter().fold(
+            client
+                .post(request.endpoint.as_ref())
+                .header(CONTENT_TYPE, FORM_CONTENT_TYPE)
+                .body(request.body()),
+            |builder, (name, value)| builder.header(*name, value.as_str()),
+        )
+    }
+
+    /// Fetch and validate the metadata document of `issuer`.
+    pub fn discover(
+        client: &Client,
+        issuer: &str,
+        kind: MetadataKind,
+    ) -> Result<AuthorizationServerMetadata, RequestError> {
+        let response = client.get(&kind.discovery_url(issuer)?).send()?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(RequestError::Status(status.as_u16()));
+        }
+        issued_metadata(issuer, &response.bytes()?)
+    }
+
+    /// Send an access token request to the token endpoint.
+    pub fn request_token(
+        client: &Client,
+        request: &EndpointRequest<'_>,
+    ) -> Result<TokenResponse, RequestError> {
+        let response = endpoint_request(client, request).send()?;
+        let status = response.status();
+        let body = endpoint_response(status, response.bytes()?.to_vec())?;
+        Ok(TokenResponse::from_slice(&body)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::v2::{BearerMethod, BearerSigner};
+    use crate::Secret;
+    use reqwest::header::AUTHORIZATION;
+
+    #[test]
+    fn test_request_error() {
+        let report = ProblemReport::from_slice(b"oauth_problem=token_rejected").unwrap();
+        let error: Box<dyn std::error::Error> = Box::new(RequestError::OAuthProblem(401, report));
+        assert_eq!(error.to_string(), "OAuth problem (401): token_rejected");
+        assert_eq!(error.source().unwrap().to_string(), "token_rejected");
+
+        let error = RequestError::from(std::io::Error::from(ErrorKind::NotFound));
+        assert!(std::error::Error::source(&error).is_some());
+        assert_eq!(
+            RequestError::Status(503).to_string(),
+            "unsuccessful response: 503"
+        );
+    }
+
+    #[test]
+    fn test_apply_signed() {
+        let token = Secret::from("mF_9.B5f-4.1JqM");
+        let signed = BearerSigner::new()
+            .sign(vec![("q".into(), "a b".into())], &token)
+            .unwrap();
+        let mut request = Client::new()
+            .get("https://server.example.com/resource?lang=ja")
+            .build()
+            .unwrap();
+        signed.apply(&mut request).unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "https://server.example.com/resource?lang=ja&q=a+b"
+        );
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer mF_9.B5f-4.1JqM");
+        assert!(request.body().is_none());
+
+        let mut signer = BearerSigner::new();
+        signer.method(BearerMethod::FormBody);
+        let signed = signer.sign(vec![], &token).unwrap();
+        let mut request = reqwest::blocking::Client::new()
+            .post("https://server.example.com/resource")
+            .build()
+            .unwrap();
+        signed.apply(&mut request).unwrap();
+        assert_eq!(request.headers()[CONTENT_TYPE], FORM_CONTENT_TYPE);
+        assert_eq!(
+            request.body().and_then(|b| b.as_bytes()),
+            Some(&b"access_token=mF_9.B5f-4.1JqM"[..])
+        );
+
+        let mut request = Client::new()
+            .get("https://server.example.com/resource?lang=ja")
+            .build()
+            .unwrap();
+        apply_dpop(
+            &mut request,
+            &DPoPKey::generate(),
+            Some("mF_9.B5f-4.1JqM"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(request.headers()[AUTHORIZATION], "DPoP mF_9.B5f-4.1JqM");
+        assert_eq!(
+            request.headers()["DPoP"]
+                .to_str()
+                .unwrap()
+                .split('.')
+                .count(),
+            3
+        );
+        assert_eq!(
+            request.url().as_str(),
+            "https://server.example.com/resource?lang=ja"
+        );
+    }
+}
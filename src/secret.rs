@@ -0,0 +1,113 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use core::fmt;
+use core::mem;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Secret string which is zeroed when dropped and redacted in `Debug` output.
+///
+/// Only owned contents can be zeroed; borrowed ones are left to their owner.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret<'a>(Cow<'a, str>);
+
+impl<'a> Secret<'a> {
+    pub fn new<T: Into<Cow<'a, str>>>(secret: T) -> Self {
+        Secret(secret.into())
+    }
+
+    /// The secret itself, to be passed where it is actually needed.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_owned(mut self) -> Secret<'static> {
+        let secret = mem::replace(&mut self.0, Cow::Borrowed(""));
+        Secret(Cow::Owned(secret.into_owned()))
+    }
+}
+
+impl Drop for Secret<'_> {
+    fn drop(&mut self) {
+        if let Cow::Owned(secret) = &mut self.0 {
+            secret.zeroize();
+        }
+    }
+}
+
+impl fmt::Debug for Secret<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<'a> From<&'a str> for Secret<'a> {
+    fn from(secret: &'a str) -> Self {
+        Secret(Cow::Borrowed(secret))
+    }
+}
+
+impl From<String> for Secret<'_> {
+    fn from(secret: String) -> Self {
+        Secret(Cow::Owned(secret))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for Secret<'a> {
+    fn from(secret: Cow<'a, str>) -> Self {
+        Secret(secret)
+    }
+}
+
+impl Serialize for Secret<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret<'static> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Secret::from)
+    }
+}
+
+/// `Debug` of key types, naming the algorithm only.
+#[cfg(feature = "std")]
+pub(crate) fn fmt_redacted_key(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    algorithm: &str,
+) -> fmt::Result {
+    write!(f, "{}({}, {})", name, algorithm, REDACTED)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::format;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_redacted() {
+        let secret = Secret::from("kd94hf93k423kf44".to_string());
+        assert_eq!(format!("{:?}", secret), "[REDACTED]");
+        assert_eq!(
+            format!("{:?}", Some(Secret::from("kd94hf93k423kf44"))),
+            "Some([REDACTED])"
+        );
+        assert_eq!(secret.expose(), "kd94hf93k423kf44");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_serde() {
+        let secret: Secret = serde_json::from_str("\"kd94hf93k423kf44\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&secret).unwrap(),
+            "\"kd94hf93k423kf44\""
+        );
+        assert_eq!(secret.into_owned().expose(), "kd94hf93k423kf44");
+    }
+}
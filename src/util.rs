@@ -1,71 +1,116 @@
-pub fn url_to_endpoint_and_queries(url: &url::Url) -> (&str, Vec<(&str, &str)>) {
-    // queries save into hashmap.
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::io;
+
+type Pair<'a> = (Cow<'a, str>, Cow<'a, str>);
+
+/// Endpoint of `url`, without the query and fragment, and its query parameters decoded.
+pub fn url_to_endpoint_and_queries(url: &url::Url) -> io::Result<(&str, Vec<Pair<'_>>)> {
     let vec = match url.query() {
-        Some(query) => destructure_query(query),
+        Some(query) => destructure_query(query)?,
         None => Vec::new(),
     };
-    let body = url.as_str().split('?').next();
-    (body.unwrap_or_else(|| url.as_str()), vec)
+    Ok((url_to_endpoint(url), vec))
+}
+
+/// Endpoint of `url`, without the query and fragment.
+pub fn url_to_endpoint(url: &url::Url) -> &str {
+    let body = url.as_str().split(['?', '#']).next();
+    body.unwrap_or_else(|| url.as_str())
 }
 
-pub fn destructure_query(query: &str) -> Vec<(&str, &str)> {
+/// Pairs of a query or `application/x-www-form-urlencoded` body in their order,
+/// decoded as RFC 5849 section 3.4.1.3.1 requires: `+` is a space, and keys
+/// without `=` have an empty value.
+///
+/// Fails if a pair does not decode to UTF-8, rather than signing replacement
+/// characters in place of the bytes which are sent.
+pub fn destructure_query(query: &str) -> io::Result<Vec<Pair<'_>>> {
     query
         .trim_start_matches('?')
         .split('&')
-        .filter(|s| !s.is_empty())
-        .map(|s| s.splitn(2, '=').collect::<Vec<&str>>())
-        .filter(|v| v.len() == 2)
-        .map(|v| (v[0], v[1]))
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((decode(key)?, decode(value)?))
+        })
         .collect()
 }
 
+fn decode(s: &str) -> io::Result<Cow<'_, str>> {
+    let invalid = |_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "query is not percent-encoded UTF-8",
+        )
+    };
+    if s.contains('+') {
+        let s = s.replace('+', " ");
+        let decoded = percent_decode_str(&s).decode_utf8().map_err(invalid)?;
+        Ok(Cow::Owned(decoded.into_owned()))
+    } else {
+        percent_decode_str(s).decode_utf8().map_err(invalid)
+    }
+}
+
+#[cfg(test)]
 mod test {
 
     use super::*;
-    use std::collections::HashMap;
+
+    fn pairs<'a>(vec: &'a [(Cow<'a, str>, Cow<'a, str>)]) -> Vec<(&'a str, &'a str)> {
+        vec.iter().map(|(k, v)| (k.as_ref(), v.as_ref())).collect()
+    }
 
     #[test]
     fn test_url_to_endpoint_and_queries() {
-        let s = "http://example.com/example+.html?quever?=salting=parsing&&&&&vir!@$========%^&*()_=askparity++++==&パラメータ=テストパラメータ";
+        let s = "http://example.com/example+.html?quever?=salting=parsing&&&&&vir!@$========%^&*()_=askparity++++==&パラメータ=テストパラメータ#top";
         let u = url::Url::parse(s).unwrap();
-        let (core, vec) = url_to_endpoint_and_queries(&u);
-        let map: HashMap<&str, &str> = vec.into_iter().collect();
+        let (core, vec) = url_to_endpoint_and_queries(&u).unwrap();
         assert_eq!(core, "http://example.com/example+.html");
-        assert_eq!(map["quever?"], "salting=parsing");
-        assert_eq!(map["vir!@$"], "=======%^");
-        assert_eq!(map["*()_"], "askparity++++==");
         assert_eq!(
-            map["%E3%83%91%E3%83%A9%E3%83%A1%E3%83%BC%E3%82%BF"],
-            "%E3%83%86%E3%82%B9%E3%83%88%E3%83%91%E3%83%A9%E3%83%A1%E3%83%BC%E3%82%BF"
+            pairs(&vec),
+            vec![
+                ("quever?", "salting=parsing"),
+                ("vir!@$", "=======%^"),
+                ("*()_", "askparity    =="),
+                ("パラメータ", "テストパラメータ"),
+            ]
         );
         let n = "https://example.com/";
         let nu = url::Url::parse(n).unwrap();
-        let (core, map2) = url_to_endpoint_and_queries(&nu);
+        let (core, map2) = url_to_endpoint_and_queries(&nu).unwrap();
         assert_eq!(core, n);
         assert_eq!(map2.len(), 0);
     }
     #[test]
-    fn test_query_to_hashmap() {
-        let map = destructure_query(
+    fn test_destructure_query() {
+        let vec = destructure_query(
             "parameter=value&!%40%23%24%25^%26*()_%2B=!%40%23%24%25^%26*()_%2B%3D",
         )
-        .into_iter()
-        .collect::<HashMap<&str, &str>>();
-        assert_eq!(map.len(), 2);
-        assert_eq!(map["parameter"], "value");
+        .unwrap();
+        assert_eq!(
+            pairs(&vec),
+            vec![("parameter", "value"), ("!@#$%^&*()_+", "!@#$%^&*()_+="),]
+        );
+        // https://tools.ietf.org/html/rfc5849#section-3.4.1.3.1
+        let vec = destructure_query("?b5=%3D%253D&a3=a&c%40=&a2=r%20b&flag&c2&a3=2+q").unwrap();
         assert_eq!(
-            map["!%40%23%24%25^%26*()_%2B"],
-            "!%40%23%24%25^%26*()_%2B%3D"
+            pairs(&vec),
+            vec![
+                ("b5", "=%3D"),
+                ("a3", "a"),
+                ("c@", ""),
+                ("a2", "r b"),
+                ("flag", ""),
+                ("c2", ""),
+                ("a3", "2 q"),
+            ]
         );
-        let map2 =
-            destructure_query("quever?=salting=parsing&&&&&vir!@$========%^&*()_=askparity++++==")
-                .into_iter()
-                .collect::<HashMap<&str, &str>>();
-        assert_eq!(map2.len(), 3);
-        assert_eq!(map2["quever?"], "salting=parsing");
-        assert_eq!(map2["vir!@$"], "=======%^");
-        assert_eq!(map2["*()_"], "askparity++++==");
-        let map3 = destructure_query("");
-        assert_eq!(map3.len(), 0);
+        assert_eq!(destructure_query("").unwrap().len(), 0);
+        // bytes which are not UTF-8 would be signed as U+FFFD otherwise.
+        let error = destructure_query("a=%FF").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(destructure_query("a=%E3%81+b").is_err());
     }
 }
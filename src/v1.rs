@@ -1,15 +1,42 @@
+#[cfg(feature = "std")]
 mod client;
+#[cfg(feature = "std")]
+mod clock;
+#[cfg(feature = "std")]
+mod nonce;
+#[cfg(feature = "std")]
+mod problem;
+#[cfg(feature = "std")]
+mod provider;
+pub mod rfc5849;
+#[cfg(feature = "std")]
 mod signer;
+#[cfg(feature = "std")]
+mod token;
 mod values;
+#[cfg(feature = "std")]
+mod xauth;
 
-pub use signer::{Secrets, Signer};
-use std::borrow::Cow;
-
-use hmac::Hmac;
-use percent_encoding::AsciiSet;
-use sha1::Sha1;
-
-use std::collections::HashMap;
-use values::*;
+#[cfg(feature = "std")]
+pub use client::OAuthV1Client;
+#[cfg(feature = "std")]
+pub use clock::{Clock, FixedClock, SystemClock};
+#[cfg(feature = "std")]
+pub use nonce::{NonceGenerator, RandomNonce, SeededNonce};
+#[cfg(feature = "std")]
+pub use problem::ProblemReport;
+#[cfg(feature = "std")]
+pub use provider::Provider;
+#[cfg(feature = "std")]
+pub use signer::{Secrets, SignedContent, Signer, OAUTH_VALUE_CALLBACK_OOB};
+#[cfg(feature = "std")]
+pub use token::TokenResponse;
+pub use values::{
+    EncodingProfile, OAuthVersion, SignatureMethod, OAUTH_VALUE_SIGMETHOD_HMACSHA1,
+    OAUTH_VALUE_SIGMETHOD_PLAINTEXT, OAUTH_VALUE_SIGMETHOD_RSASHA1, OAUTH_VALUE_VERSION,
+};
+#[cfg(feature = "std")]
+pub use xauth::OAUTH_VALUE_X_AUTH_MODE_CLIENT_AUTH;
 
+#[cfg(feature = "std")]
 const OAUTH_HEADER: &str = "OAuth";
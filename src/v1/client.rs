@@ -1,11 +1,205 @@
-use crate::v1;
-
-/// OAuth V1 Signature Generator
-pub struct OAuthV1Client<T> {
-    oauth_token: T,
-    oauth_consumer_key: String,
-    oauth_nonce: String,
-    oauth_signature_method: String,
-    oauth_version: Option<String>,
-    oauth_timestamp: Option<i64>,
+use crate::builder::OAuthSigner;
+use crate::credentials::{ConsumerCredentials, TokenCredentials};
+use crate::parameters::{OAuthParameter, OAuthSignedContent};
+use crate::v1::rfc5849::OAUTH_PARAM_KEY_REALM;
+use crate::v1::{SignatureMethod, Signer};
+use std::borrow::Cow;
+use std::io;
+
+/// OAuth 1 client signing every request with the same credentials.
+///
+/// Requests are sent with `get`, `post` and `request` of the HTTP bridge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OAuthV1Client {
+    consumer: ConsumerCredentials,
+    token: Option<TokenCredentials>,
+    signature_method: SignatureMethod,
+    realm: Option<String>,
+}
+
+impl OAuthV1Client {
+    /// Client making requests with the consumer credentials only.
+    pub fn new(consumer: ConsumerCredentials) -> Self {
+        OAuthV1Client {
+            consumer,
+            token: None,
+            signature_method: SignatureMethod::HmacSha1,
+            realm: None,
+        }
+    }
+
+    /// Client making requests on behalf of the owner of `token`.
+    pub fn with_token(consumer: ConsumerCredentials, token: TokenCredentials) -> Self {
+        OAuthV1Client {
+            token: Some(token),
+            ..OAuthV1Client::new(consumer)
+        }
+    }
+
+    pub fn token(&mut self, token: TokenCredentials) -> &mut Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub fn signature_method(&mut self, signature_method: SignatureMethod) -> &mut Self {
+        self.signature_method = signature_method;
+        self
+    }
+
+    pub fn realm<T: Into<String>>(&mut self, realm: T) -> &mut Self {
+        self.realm = Some(realm.into());
+        self
+    }
+
+    pub fn consumer_credentials(&self) -> &ConsumerCredentials {
+        &self.consumer
+    }
+
+    pub fn token_credentials(&self) -> Option<&TokenCredentials> {
+        self.token.as_ref()
+    }
+
+    /// Sign a `http_method` request to `url` with `params`.
+    ///
+    /// The query of `url` is signed too, but stays in `url`: the parameters
+    /// of the returned content are `params` only. With file parameters, `params`
+    /// are a `multipart/form-data` body and are not signed.
+    ///
+    /// The protocol parameters are the client's own: a query carrying `realm` or
+    /// an `oauth_` parameter is an `InvalidInput` error.
+    pub fn sign<'a>(
+        &'a self,
+        http_method: &'a str,
+        url: &'a url::Url,
+        params: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
+    ) -> io::Result<OAuthSignedContent> {
+        if let Some((key, _)) = url
+            .query_pairs()
+            .find(|(k, _)| k == OAUTH_PARAM_KEY_REALM || k.starts_with("oauth_"))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} can not be sent in the URL", key),
+            ));
+        }
+        let endpoint = url.as_str();
+        let signed = match &self.token {
+            Some(token) => {
+                let mut signer = Signer::<Cow<str>>::new(
+                    self.consumer.key.as_str(),
+                    endpoint,
+                    http_method,
+                    token.token.as_str(),
+                );
+                signer.signature_method(self.signature_method);
+                if let Some(realm) = &self.realm {
+                    signer.realm(realm.as_str());
+                }
+                signer.sign(params, &self.consumer.secrets_with(token))?
+            }
+            None => {
+                let mut signer =
+                    Signer::<()>::new(self.consumer.key.as_str(), endpoint, http_method);
+                signer.signature_method(self.signature_method);
+                if let Some(realm) = &self.realm {
+                    signer.realm(realm.as_str());
+                }
+                signer.sign(params, &self.consumer.secrets())?
+            }
+        };
+        Ok(signed.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::OAuthSignedParameter;
+
+    #[test]
+    fn test_sign_request() {
+        let mut client = OAuthV1Client::with_token(
+            ConsumerCredentials::new("dpf43f3p2l4k3l03", "kd94hf93k423kf44"),
+            TokenCredentials::new("nnch734d00sl2jdk", "pfkkdhi9sl3r4s00"),
+        );
+        client.realm("Photos");
+        let url = url::Url::parse("http://photos.example.net/photos?file=vacation.jpg").unwrap();
+        let signed = client
+            .sign("POST", &url, vec![("size".into(), "original".into())])
+            .unwrap();
+
+        assert_eq!(
+            signed.parameters,
+            vec![(
+                "size".to_string(),
+                OAuthSignedParameter::StringValue("original".to_string())
+            )]
+        );
+        let protocol = |key: &str| {
+            signed
+                .protocol_parameters
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(protocol("oauth_token"), Some("nnch734d00sl2jdk"));
+        assert_eq!(protocol("oauth_consumer_key"), Some("dpf43f3p2l4k3l03"));
+        assert_eq!(protocol("realm"), Some("Photos"));
+        assert!(signed.signature.is_some());
+        assert_eq!(signed.headers[0].0, "Authorization");
+        assert!(signed.headers[0].1.contains("realm=\"Photos\""));
+    }
+
+    #[test]
+    fn test_sign_with_signer() {
+        // the client signs the query and the parameters like a signer given both.
+        let client = OAuthV1Client::new(ConsumerCredentials::new("key", "secret"));
+        let url = url::Url::parse("https://api.example.com/search?q=a%20b").unwrap();
+        let signed = client
+            .sign("GET", &url, vec![("count".into(), 5.into())])
+            .unwrap();
+        let nonce = signed
+            .protocol_parameters
+            .iter()
+            .find(|(k, _)| k == "oauth_nonce")
+            .map(|(_, v)| v.clone())
+            .unwrap();
+        let timestamp = signed
+            .protocol_parameters
+            .iter()
+            .find(|(k, _)| k == "oauth_timestamp")
+            .map(|(_, v)| v.parse().unwrap())
+            .unwrap();
+
+        let mut signer = Signer::<()>::new("key", "https://api.example.com/search", "GET");
+        signer.nonce(nonce).timestamp(timestamp);
+        let expected = signer
+            .sign(
+                vec![("q".into(), "a b".into()), ("count".into(), 5.into())],
+                &client.consumer_credentials().secrets(),
+            )
+            .unwrap();
+        assert_eq!(signed.signature, Some(expected.signature));
+        assert_eq!(
+            signed.parameters,
+            vec![(
+                "count".to_string(),
+                OAuthSignedParameter::StringValue("5".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_reserved_query() {
+        let client = OAuthV1Client::with_token(
+            ConsumerCredentials::new("key", "secret"),
+            TokenCredentials::new("token", "token secret"),
+        );
+        for query in &["oauth_token=other", "realm=Photos", "q=a&oauth_nonce=1"] {
+            let url =
+                url::Url::parse(&format!("https://api.example.com/search?{}", query)).unwrap();
+            let e = client.sign("GET", &url, vec![]).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput, "{}", query);
+        }
+    }
 }
@@ -0,0 +1,38 @@
+use chrono::Utc;
+
+/// Source of `oauth_timestamp` values.
+pub trait Clock: Send + Sync {
+    /// Seconds since the UNIX epoch.
+    fn now(&self) -> i64;
+}
+
+/// The system time; the default clock of signers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        Utc::now().timestamp()
+    }
+}
+
+/// Clock stopped at a given time, for reproducible signatures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedClock(pub i64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> i64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_clocks() {
+        assert_eq!(FixedClock(1_318_622_958).now(), 1_318_622_958);
+        assert!(SystemClock.now() > 1_318_622_958);
+    }
+}
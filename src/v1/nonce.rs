@@ -0,0 +1,83 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use uuid::Uuid;
+
+const SPLITMIX64_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Source of `oauth_nonce` values.
+pub trait NonceGenerator: Send + Sync {
+    fn generate(&self) -> String;
+}
+
+/// Random UUIDs; the default nonce generator of signers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RandomNonce;
+
+impl NonceGenerator for RandomNonce {
+    fn generate(&self) -> String {
+        Uuid::new_v4().to_string()
+    }
+}
+
+/// Reproducible sequence of nonces derived from a seed, for tests.
+///
+/// The nonces are not suitable for production use: they are predictable.
+#[derive(Debug)]
+pub struct SeededNonce {
+    seed: u64,
+    count: AtomicU64,
+}
+
+impl SeededNonce {
+    pub fn new(seed: u64) -> Self {
+        SeededNonce {
+            seed,
+            count: AtomicU64::new(0),
+        }
+    }
+
+    // https://prng.di.unimi.it/splitmix64.c
+    fn splitmix64(&self, index: u64) -> u64 {
+        let mut z = self
+            .seed
+            .wrapping_add(index.wrapping_add(1).wrapping_mul(SPLITMIX64_GAMMA));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl NonceGenerator for SeededNonce {
+    fn generate(&self) -> String {
+        // two outputs of the generator as 32 hex digits.
+        let index = self.count.fetch_add(2, Ordering::Relaxed);
+        format!(
+            "{:016x}{:016x}",
+            self.splitmix64(index),
+            self.splitmix64(index + 1)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_seeded_nonce() {
+        let generator = SeededNonce::new(42);
+        let first = generator.generate();
+        let second = generator.generate();
+        assert_eq!(first.len(), 32);
+        assert_ne!(first, second);
+
+        let replay = SeededNonce::new(42);
+        assert_eq!(replay.generate(), first);
+        assert_eq!(replay.generate(), second);
+        assert_ne!(SeededNonce::new(43).generate(), first);
+    }
+
+    #[test]
+    fn test_random_nonce() {
+        assert_ne!(RandomNonce.generate(), RandomNonce.generate());
+    }
+}
@@ -0,0 +1,195 @@
+use percent_encoding::percent_decode_str;
+use std::fmt;
+use url::form_urlencoded;
+
+const OAUTH_PARAM_KEY_PROBLEM: &str = "oauth_problem";
+const OAUTH_PARAM_KEY_PROBLEM_ADVICE: &str = "oauth_problem_advice";
+const OAUTH_AUTH_SCHEME: &str = "OAuth";
+
+/// Error reported by an OAuth 1 service, e.g. `token_rejected` or `signature_invalid`.
+///
+/// https://wiki.oauth.net/w/page/12238543/ProblemReporting
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProblemReport {
+    pub problem: String,
+    pub advice: Option<String>,
+    /// Other parameters of the report, e.g. `oauth_acceptable_timestamps`.
+    pub extra: Vec<(String, String)>,
+}
+
+impl ProblemReport {
+    /// Parse an `application/x-www-form-urlencoded` response body,
+    /// `None` if `oauth_problem` is missing.
+    pub fn from_slice(body: &[u8]) -> Option<Self> {
+        ProblemReport::from_pairs(form_urlencoded::parse(body).into_owned())
+    }
+
+    /// Parse the parameters of an `OAuth` challenge in a `WWW-Authenticate` header,
+    /// `None` if the challenge has no `oauth_problem`.
+    pub fn from_www_authenticate(header: &str) -> Option<Self> {
+        let header = header.trim_start();
+        let scheme = header.get(..OAUTH_AUTH_SCHEME.len())?;
+        let params = &header[OAUTH_AUTH_SCHEME.len()..];
+        if !scheme.eq_ignore_ascii_case(OAUTH_AUTH_SCHEME)
+            || !(params.is_empty() || params.starts_with(|c: char| c.is_ascii_whitespace()))
+        {
+            return None;
+        }
+        let pairs = auth_params(params).into_iter().map(|(key, value)| {
+            let value = percent_decode_str(&value).decode_utf8_lossy().into_owned();
+            (key.to_string(), value)
+        });
+        ProblemReport::from_pairs(pairs)
+    }
+
+    fn from_pairs<I: IntoIterator<Item = (String, String)>>(pairs: I) -> Option<Self> {
+        let mut problem = None;
+        let mut advice = None;
+        let mut extra = Vec::new();
+        for (key, value) in pairs {
+            match key.as_str() {
+                OAUTH_PARAM_KEY_PROBLEM => problem = Some(value),
+                OAUTH_PARAM_KEY_PROBLEM_ADVICE => advice = Some(value),
+                _ => extra.push((key, value)),
+            }
+        }
+        Some(ProblemReport {
+            problem: problem?,
+            advice,
+            extra,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.extra
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// `key=value` parameters of a challenge (RFC 7235 section 2.1), whose values
+/// are tokens or quoted strings; parsing stops at the first malformed one.
+fn auth_params(params: &str) -> Vec<(&str, String)> {
+    let mut pairs = Vec::new();
+    let mut rest = params;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_ascii_whitespace());
+        let key_end = match rest.find(|c: char| c == '=' || c == ',' || c.is_ascii_whitespace()) {
+            Some(end) if end > 0 => end,
+            _ => break,
+        };
+        let key = &rest[..key_end];
+        rest = rest[key_end..].trim_start();
+        rest = match rest.strip_prefix('=') {
+            Some(rest) => rest.trim_start(),
+            None => break,
+        };
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        end = Some(i + 1);
+                        break;
+                    }
+                    '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                    c => value.push(c),
+                }
+            }
+            match end {
+                Some(end) => rest = &quoted[end..],
+                None => break,
+            }
+            value
+        } else {
+            let end = rest
+                .find(|c: char| c == ',' || c.is_ascii_whitespace())
+                .unwrap_or(rest.len());
+            let value = rest[..end].to_string();
+            rest = &rest[end..];
+            value
+        };
+        pairs.push((key, value));
+    }
+    pairs
+}
+
+impl fmt::Display for ProblemReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.advice {
+            Some(advice) => write!(f, "{}: {}", self.problem, advice),
+            None => f.write_str(&self.problem),
+        }
+    }
+}
+
+impl std::error::Error for ProblemReport {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_problem() {
+        let report = ProblemReport::from_slice(
+            b"oauth_problem=timestamp_refused&oauth_acceptable_timestamps=1191242090-1191242096",
+        )
+        .unwrap();
+        assert_eq!(report.problem, "timestamp_refused");
+        assert_eq!(report.advice, None);
+        assert_eq!(
+            report.get("oauth_acceptable_timestamps"),
+            Some("1191242090-1191242096")
+        );
+        assert_eq!(ProblemReport::from_slice(b"error=unknown"), None);
+
+        let report = ProblemReport::from_www_authenticate(
+            "OAuth realm=\"Photos\", oauth_problem=\"token_rejected\", \
+             oauth_problem_advice=\"Token%20expired\"",
+        )
+        .unwrap();
+        assert_eq!(report.problem, "token_rejected");
+        assert_eq!(report.advice.as_deref(), Some("Token expired"));
+        assert_eq!(report.get("realm"), Some("Photos"));
+        assert_eq!(report.to_string(), "token_rejected: Token expired");
+        assert_eq!(
+            ProblemReport::from_www_authenticate("Bearer error=\"invalid_token\""),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_challenge() {
+        let report = ProblemReport::from_www_authenticate(
+            "oauth realm=\"Photos, \\\"Inc.\\\"\",oauth_problem=parameter_absent, \
+             oauth_parameters_absent=\"oauth_nonce,oauth_timestamp\"",
+        )
+        .unwrap();
+        assert_eq!(report.problem, "parameter_absent");
+        assert_eq!(report.get("realm"), Some("Photos, \"Inc.\""));
+        assert_eq!(
+            report.get("oauth_parameters_absent"),
+            Some("oauth_nonce,oauth_timestamp")
+        );
+
+        for header in &[
+            "OAuthX oauth_problem=\"token_rejected\"",
+            "OAuté oauth_problem=\"token_rejected\"",
+            "OAu",
+            "OAuth",
+            "OAuth oauth_problem=\"token_rejected",
+        ] {
+            assert_eq!(
+                ProblemReport::from_www_authenticate(header),
+                None,
+                "{}",
+                header
+            );
+        }
+        let error: Box<dyn std::error::Error> = Box::new(report);
+        assert_eq!(error.to_string(), "parameter_absent");
+    }
+}
@@ -0,0 +1,347 @@
+use crate::v1::{EncodingProfile, OAuthVersion, SignatureMethod, Signer};
+use std::borrow::Cow;
+
+const OAUTH_PARAM_KEY_TOKEN: &str = "oauth_token";
+
+/// Endpoints and signing conventions of an OAuth 1 service.
+///
+/// The built-in presets are mostly a table of endpoints: every service but
+/// Jira signs with the defaults of `Signer` (HMAC-SHA1, `oauth_version=1.0`,
+/// no `realm`, strict RFC 3986 encoding), and Jira only differs by RSA-SHA1.
+/// The other fields are there for services configured by hand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Provider<'a> {
+    pub name: Cow<'a, str>,
+    pub request_token_url: Cow<'a, str>,
+    pub authorize_url: Cow<'a, str>,
+    pub access_token_url: Cow<'a, str>,
+    pub signature_method: SignatureMethod,
+    pub version: OAuthVersion<'a>,
+    /// `realm` announced with every request, if the service expects one.
+    pub realm: Option<Cow<'a, str>>,
+    pub param_encoding: EncodingProfile,
+    pub base_string_encoding: EncodingProfile,
+}
+
+impl Provider<'static> {
+    /// Service at these endpoints which signs with the defaults of `Signer`.
+    fn preset(
+        name: &'static str,
+        request_token_url: &'static str,
+        authorize_url: &'static str,
+        access_token_url: &'static str,
+    ) -> Self {
+        Provider {
+            name: name.into(),
+            request_token_url: request_token_url.into(),
+            authorize_url: authorize_url.into(),
+            access_token_url: access_token_url.into(),
+            signature_method: SignatureMethod::HmacSha1,
+            version: OAuthVersion::Default,
+            realm: None,
+            param_encoding: EncodingProfile::Rfc3986,
+            base_string_encoding: EncodingProfile::Rfc3986,
+        }
+    }
+
+    // https://developer.twitter.com/en/docs/authentication/oauth-1-0a
+    pub fn twitter() -> Self {
+        Provider::preset(
+            "twitter",
+            "https://api.twitter.com/oauth/request_token",
+            "https://api.twitter.com/oauth/authorize",
+            "https://api.twitter.com/oauth/access_token",
+        )
+    }
+
+    // https://www.flickr.com/services/api/auth.oauth.html
+    pub fn flickr() -> Self {
+        Provider::preset(
+            "flickr",
+            "https://www.flickr.com/services/oauth/request_token",
+            "https://www.flickr.com/services/oauth/authorize",
+            "https://www.flickr.com/services/oauth/access_token",
+        )
+    }
+
+    // https://www.tumblr.com/docs/en/api/v2#oauth1-authorization
+    pub fn tumblr() -> Self {
+        Provider::preset(
+            "tumblr",
+            "https://www.tumblr.com/oauth/request_token",
+            "https://www.tumblr.com/oauth/authorize",
+            "https://www.tumblr.com/oauth/access_token",
+        )
+    }
+
+    // https://developer.atlassian.com/cloud/trello/guides/rest-api/authorization/
+    pub fn trello() -> Self {
+        Provider::preset(
+            "trello",
+            "https://trello.com/1/OAuthGetRequestToken",
+            "https://trello.com/1/OAuthAuthorizeToken",
+            "https://trello.com/1/OAuthGetAccessToken",
+        )
+    }
+
+    /// Garmin Connect (Health and Activity APIs).
+    pub fn garmin() -> Self {
+        Provider::preset(
+            "garmin",
+            "https://connectapi.garmin.com/oauth-service/oauth/request_token",
+            "https://connect.garmin.com/oauthConfirm",
+            "https://connectapi.garmin.com/oauth-service/oauth/access_token",
+        )
+    }
+
+    /// Etsy API v2; v3 uses OAuth 2.
+    pub fn etsy_v2() -> Self {
+        Provider::preset(
+            "etsy",
+            "https://openapi.etsy.com/v2/oauth/request_token",
+            "https://www.etsy.com/oauth/signin",
+            "https://openapi.etsy.com/v2/oauth/access_token",
+        )
+    }
+
+    /// Jira Server or Data Center at `base_url`, e.g. `https://jira.example.com`.
+    ///
+    /// Jira only accepts RSA-SHA1, so the consumer secret is the private key
+    /// whose public key is registered in the application link.
+    pub fn jira(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        Provider {
+            name: "jira".into(),
+            request_token_url: format!("{}/plugins/servlet/oauth/request-token", base_url).into(),
+            authorize_url: format!("{}/plugins/servlet/oauth/authorize", base_url).into(),
+            access_token_url: format!("{}/plugins/servlet/oauth/access-token", base_url).into(),
+            signature_method: SignatureMethod::RsaSha1,
+            version: OAuthVersion::Default,
+            realm: None,
+            param_encoding: EncodingProfile::Rfc3986,
+            base_string_encoding: EncodingProfile::Rfc3986,
+        }
+    }
+
+    /// The preset named `name`, except `jira` which needs a base URL.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "twitter" => Some(Provider::twitter()),
+            "flickr" => Some(Provider::flickr()),
+            "tumblr" => Some(Provider::tumblr()),
+            "trello" => Some(Provider::trello()),
+            "garmin" => Some(Provider::garmin()),
+            "etsy" => Some(Provider::etsy_v2()),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Provider<'a> {
+    /// Apply the signing conventions of this service to `signer`.
+    pub fn configure<'s, T>(&self, signer: &'s mut Signer<'a, T>) -> &'s mut Signer<'a, T> {
+        signer
+            .signature_method(self.signature_method)
+            .version(self.version.clone())
+            .param_encoding(self.param_encoding)
+            .base_string_encoding(self.base_string_encoding);
+        if let Some(realm) = &self.realm {
+            signer.realm(realm.clone());
+        }
+        signer
+    }
+
+    /// Signer for a request made with the consumer credentials only.
+    pub fn signer<TConsumerKey, TEndpoint, THttpMethod>(
+        &self,
+        consumer_key: TConsumerKey,
+        endpoint: TEndpoint,
+        http_method: THttpMethod,
+    ) -> Signer<'a, ()>
+    where
+        TConsumerKey: Into<Cow<'a, str>>,
+        TEndpoint: Into<Cow<'a, str>>,
+        THttpMethod: Into<Cow<'a, str>>,
+    {
+        let mut signer = Signer::<()>::new(consumer_key, endpoint, http_method);
+        self.configure(&mut signer);
+        signer
+    }
+
+    /// Signer for a request made on behalf of the owner of `token`.
+    pub fn signer_with_token<TConsumerKey, TEndpoint, THttpMethod, TToken>(
+        &self,
+        consumer_key: TConsumerKey,
+        endpoint: TEndpoint,
+        http_method: THttpMethod,
+        token: TToken,
+    ) -> Signer<'a, Cow<'a, str>>
+    where
+        TConsumerKey: Into<Cow<'a, str>>,
+        TEndpoint: Into<Cow<'a, str>>,
+        THttpMethod: Into<Cow<'a, str>>,
+        TToken: Into<Cow<'a, str>>,
+    {
+        let mut signer = Signer::<Cow<str>>::new(consumer_key, endpoint, http_method, token);
+        self.configure(&mut signer);
+        signer
+    }
+
+    /// Signer of the temporary credential request (`POST` to the request token URL).
+    pub fn request_token_signer<T: Into<Cow<'a, str>>>(&self, consumer_key: T) -> Signer<'a, ()> {
+        self.signer(consumer_key, self.request_token_url.clone(), "POST")
+    }
+
+    /// URL to send the resource owner to with the temporary credentials.
+    pub fn authorize_url(&self, temporary_token: &str) -> Result<String, url::ParseError> {
+        let mut url = url::Url::parse(&self.authorize_url)?;
+        url.query_pairs_mut()
+            .append_pair(OAUTH_PARAM_KEY_TOKEN, temporary_token);
+        Ok(url.into())
+    }
+
+    /// Signer of the token request (`POST` to the access token URL).
+    pub fn access_token_signer<TConsumerKey, TToken>(
+        &self,
+        consumer_key: TConsumerKey,
+        temporary_token: TToken,
+    ) -> Signer<'a, Cow<'a, str>>
+    where
+        TConsumerKey: Into<Cow<'a, str>>,
+        TToken: Into<Cow<'a, str>>,
+    {
+        self.signer_with_token(
+            consumer_key,
+            self.access_token_url.clone(),
+            "POST",
+            temporary_token,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::v1::Secrets;
+    use crate::OAuthSigner;
+
+    #[test]
+    fn test_presets() {
+        // none of the services has a signing quirk but Jira.
+        let conventions = |p: &Provider<'static>| {
+            (
+                p.signature_method,
+                p.version.clone(),
+                p.realm.clone(),
+                p.param_encoding,
+                p.base_string_encoding,
+            )
+        };
+        let mut expected = (
+            SignatureMethod::HmacSha1,
+            OAuthVersion::Default,
+            None,
+            EncodingProfile::Rfc3986,
+            EncodingProfile::Rfc3986,
+        );
+        for name in &["twitter", "flickr", "tumblr", "trello", "garmin", "etsy"] {
+            let provider = Provider::from_name(name).unwrap();
+            for url in &[
+                &provider.request_token_url,
+                &provider.authorize_url,
+                &provider.access_token_url,
+            ] {
+                assert!(url.starts_with("https://"), "{}: {}", name, url);
+            }
+            assert_eq!(conventions(&provider), expected, "{}", name);
+        }
+        assert!(Provider::from_name("jira").is_none());
+
+        let jira = Provider::jira("https://jira.example.com/");
+        assert_eq!(
+            jira.access_token_url,
+            "https://jira.example.com/plugins/servlet/oauth/access-token"
+        );
+        expected.0 = SignatureMethod::RsaSha1;
+        assert_eq!(conventions(&jira), expected);
+    }
+
+    #[test]
+    fn test_twitter_signature() {
+        // https://developer.twitter.com/en/docs/basics/authentication/guides/creating-a-signature
+        let sign = |provider: &Provider<'static>| {
+            let mut signer = provider.signer_with_token(
+                "xvz1evFS4wEEPTGEFPHBog",
+                "https://api.twitter.com/1.1/statuses/update.json?include_entities=true",
+                "POST",
+                "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb",
+            );
+            signer
+                .nonce("kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg")
+                .timestamp(1_318_622_958);
+            signer
+                .sign(
+                    vec![(
+                        "status".into(),
+                        "Hello Ladies + Gentlemen, a signed OAuth request!".into(),
+                    )],
+                    &Secrets::<crate::Secret>::new(
+                        "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw",
+                        "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE",
+                    ),
+                )
+                .unwrap()
+        };
+        let signed = sign(&Provider::twitter());
+        assert_eq!(signed.signature, "hCtSmYh+iHYCEqBWrE7C7hYmtUk=");
+        assert!(signed
+            .base_string
+            .starts_with("POST&https%3A%2F%2Fapi.twitter.com%2F1.1%2Fstatuses%2Fupdate.json&"));
+
+        // leaving `:` as is does not produce the base string Twitter expects.
+        let keep_colon = sign(&Provider {
+            param_encoding: EncodingProfile::KeepColon,
+            base_string_encoding: EncodingProfile::KeepColon,
+            ..Provider::twitter()
+        });
+        assert!(keep_colon
+            .base_string
+            .starts_with("POST&https:%2F%2Fapi.twitter.com"));
+        assert_ne!(keep_colon.signature, signed.signature);
+    }
+
+    #[test]
+    fn test_three_legged_signers() {
+        let provider = Provider {
+            realm: Some("Photos".into()),
+            ..Provider::flickr()
+        };
+        let mut signer = provider.request_token_signer("dpf43f3p2l4k3l03");
+        signer.nonce("wIjqoS").timestamp(137_131_200);
+        let signed = signer
+            .sign(vec![], &Secrets::<()>::new("kd94hf93k423kf44"))
+            .unwrap();
+        assert!(signed
+            .base_string
+            .starts_with("POST&https%3A%2F%2Fwww.flickr.com%2Fservices%2Foauth%2Frequest_token&"));
+        assert!(signed
+            .authorization_header()
+            .contains("oauth_version=\"1.0\", realm=\"Photos\""));
+        assert!(!signed.base_string.contains("realm"));
+
+        assert_eq!(
+            provider.authorize_url("hh5s93j4hdidpola").unwrap(),
+            "https://www.flickr.com/services/oauth/authorize?oauth_token=hh5s93j4hdidpola"
+        );
+        let signed = provider
+            .access_token_signer("dpf43f3p2l4k3l03", "hh5s93j4hdidpola")
+            .sign(
+                vec![],
+                &Secrets::<crate::Secret>::new("kd94hf93k423kf44", "hdhd0244k9j7ao03"),
+            )
+            .unwrap();
+        assert!(signed
+            .payload
+            .contains(&("oauth_token".into(), "hh5s93j4hdidpola".into())));
+    }
+}
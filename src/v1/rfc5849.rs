@@ -0,0 +1,424 @@
+//! Signatures of RFC 5849 with `alloc` only, e.g. for `no_std` targets.
+//!
+//! The caller supplies the nonce and the timestamp. Files are neither signed
+//! nor read to sign, since they are parts of a `multipart/form-data` body
+//! (RFC 5849 section 3.4.1.3.1). `Signer` builds on this with the `std` feature.
+use crate::secret::Secret;
+use crate::v1::{EncodingProfile, OAuthVersion, SignatureMethod};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use hmac::{Hmac, Mac};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, PercentEncode};
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs8::DecodePrivateKey;
+use sha1::{Digest, Sha1};
+
+type HmacSha1 = Hmac<Sha1>;
+
+// DER prefix of the DigestInfo of SHA-1 digests (RFC 8017 section 9.2).
+pub(crate) const SHA1_DIGEST_INFO_PREFIX: &[u8] = &[
+    0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
+];
+
+const OAUTH_PARAM_KEY_CALLBACK: &str = "oauth_callback";
+const OAUTH_PARAM_KEY_CONSUMER_KEY: &str = "oauth_consumer_key";
+const OAUTH_PARAM_KEY_NONCE: &str = "oauth_nonce";
+pub(crate) const OAUTH_PARAM_KEY_REALM: &str = "realm";
+const OAUTH_PARAM_KEY_SIGNATURE_METHOD: &str = "oauth_signature_method";
+const OAUTH_PARAM_KEY_TIMESTAMP: &str = "oauth_timestamp";
+const OAUTH_PARAM_KEY_TOKEN: &str = "oauth_token";
+const OAUTH_PARAM_KEY_VERIFIER: &str = "oauth_verifier";
+const OAUTH_PARAM_KEY_VERSION: &str = "oauth_version";
+
+// https://tools.ietf.org/html/rfc5849#section-3.6
+// * ALPHA, DIGIT, '-', '.', '_', '~' MUST NOT be encoded.
+// * All other characters MUST be encoded.
+// * The two hexadecimal characters used to represent encoded
+//   characters MUST be uppercase.
+pub(crate) const TARGETS_FOR_PARAMS: &AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+// This is also used for generating signatures.
+const TARGETS_FOR_SIGN: &AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+const TARGETS_KEEPING_COLON: &AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b':'); // 🤔🤔🤔🤔🤔🤔🤔🤔🤔??????????
+
+/// Protocol parameters of one request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolParameters<'a> {
+    pub consumer_key: Cow<'a, str>,
+    pub token: Option<Cow<'a, str>>,
+    pub signature_method: SignatureMethod,
+    pub nonce: Cow<'a, str>,
+    /// Seconds since the UNIX epoch.
+    pub timestamp: i64,
+    pub version: OAuthVersion<'a>,
+    pub callback: Option<Cow<'a, str>>,
+    pub verifier: Option<Cow<'a, str>>,
+}
+
+impl<'a> ProtocolParameters<'a> {
+    /// HMAC-SHA1 signed parameters with `oauth_version=1.0` and no token.
+    pub fn new<TConsumerKey, TNonce>(
+        consumer_key: TConsumerKey,
+        nonce: TNonce,
+        timestamp: i64,
+    ) -> Self
+    where
+        TConsumerKey: Into<Cow<'a, str>>,
+        TNonce: Into<Cow<'a, str>>,
+    {
+        ProtocolParameters {
+            consumer_key: consumer_key.into(),
+            token: None,
+            signature_method: SignatureMethod::HmacSha1,
+            nonce: nonce.into(),
+            timestamp,
+            version: OAuthVersion::Default,
+            callback: None,
+            verifier: None,
+        }
+    }
+}
+
+/// Signature of a request and the parameters it covers.
+pub struct Signature<'a> {
+    /// Not percent-encoded.
+    pub signature: String,
+    /// Percent-encoded protocol parameters, sorted, then the others in their given order.
+    pub payload: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    /// Signature base string (https://tools.ietf.org/html/rfc5849#section-3.4.1).
+    pub base_string: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignError {
+    /// The consumer secret of RSA-SHA1 is not a PEM encoded RSA private key.
+    InvalidRsaKey,
+    Rsa(String),
+}
+
+impl fmt::Display for SignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignError::InvalidRsaKey => f.write_str(
+                "RSA-SHA1 requires a PEM encoded RSA private key as the consumer secret",
+            ),
+            SignError::Rsa(e) => write!(f, "RSA-SHA1 signing failed: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SignError {}
+
+#[cfg(feature = "std")]
+impl From<SignError> for std::io::Error {
+    fn from(e: SignError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+    }
+}
+
+/// Sign a `http_method` request to `endpoint` with `parameters`, which are not
+/// percent-encoded. `realm` is not signed.
+///
+/// The query of `endpoint` is signed too (RFC 5849 section 3.4.1.3.1), but is
+/// not put in the payload since it is sent in the URL.
+pub fn sign<'a>(
+    http_method: &str,
+    endpoint: &str,
+    protocol: ProtocolParameters<'a>,
+    parameters: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    (consumer_secret, token_secret): (&str, Option<&str>),
+    (param_encoding, base_string_encoding): (EncodingProfile, EncodingProfile),
+) -> Result<Signature<'a>, SignError> {
+    let param_targets = targets_for_params(param_encoding);
+    let sign_targets = targets_for_sign(base_string_encoding);
+    let signature_method = protocol.signature_method;
+    let (endpoint, query) = split_query(endpoint, param_targets);
+
+    let mut basic_params_encoded = build_basic_params(protocol, param_targets);
+    let user_params_encoded = parameters
+        .into_iter()
+        .map(|(k, v)| {
+            (
+                percent_encode_cow(k, param_targets),
+                percent_encode_cow(v, param_targets),
+            )
+        })
+        .collect::<Vec<_>>();
+    // the protocol parameters come first, then the others in the order they were given.
+    basic_params_encoded.sort();
+    let payload = [basic_params_encoded, user_params_encoded].concat::<(Cow<'a, str>, Cow<str>)>();
+    // the base string sorts by the encoded names, then values (RFC 5849 section 3.4.1.3.2)
+    let mut sorted = payload.clone();
+    sorted.extend(query.into_iter().map(|(k, v)| (Cow::from(k), Cow::from(v))));
+    sorted.sort();
+
+    let base_string = generate_base_string(http_method, endpoint, &sorted, sign_targets);
+    let signature = match signature_method {
+        SignatureMethod::PlainText => generate_signature_plaintext(consumer_secret, token_secret),
+        SignatureMethod::HmacSha1 => {
+            generate_signature_hmacsha1(consumer_secret, token_secret, &base_string, sign_targets)
+        }
+        SignatureMethod::RsaSha1 => generate_signature_rsasha1(consumer_secret, &base_string)?,
+    };
+    Ok(Signature {
+        signature,
+        payload,
+        base_string,
+    })
+}
+
+/// `endpoint` without its query and fragment, and the parameters of its query
+/// decoded as an `application/x-www-form-urlencoded` string, then encoded with
+/// `targets`.
+///
+/// The decoded bytes are encoded as they are, so that those which are not
+/// UTF-8 are signed as they are sent.
+fn split_query<'a>(
+    endpoint: &'a str,
+    targets: &'static AsciiSet,
+) -> (&'a str, Vec<(String, String)>) {
+    let endpoint = endpoint.split('#').next().unwrap_or(endpoint);
+    let (endpoint, query) = match endpoint.split_once('?') {
+        Some(split) => split,
+        None => return (endpoint, Vec::new()),
+    };
+    let decode = |s: &str| {
+        let bytes = percent_decode_str(&s.replace('+', " ")).collect::<Vec<u8>>();
+        percent_encoding::percent_encode(&bytes, targets).to_string()
+    };
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect();
+    (endpoint, query)
+}
+
+fn build_basic_params<'a>(
+    protocol: ProtocolParameters<'a>,
+    targets: &'static AsciiSet,
+) -> Vec<(Cow<'a, str>, Cow<'a, str>)> {
+    // build authorization basic parameters
+    let params = vec![
+        // owned parameters
+        (
+            OAUTH_PARAM_KEY_TIMESTAMP,
+            Some(Cow::Owned(format!("{}", protocol.timestamp))),
+        ),
+        // borrowed parameters
+        (OAUTH_PARAM_KEY_CONSUMER_KEY, Some(protocol.consumer_key)),
+        (
+            OAUTH_PARAM_KEY_SIGNATURE_METHOD,
+            Some(Cow::Borrowed(protocol.signature_method.into())),
+        ),
+        (OAUTH_PARAM_KEY_NONCE, Some(protocol.nonce)),
+        // noneable borrowed parameters
+        (OAUTH_PARAM_KEY_VERSION, protocol.version.into()),
+        (OAUTH_PARAM_KEY_TOKEN, protocol.token),
+        (OAUTH_PARAM_KEY_CALLBACK, protocol.callback),
+        (OAUTH_PARAM_KEY_VERIFIER, protocol.verifier),
+    ];
+
+    params
+        .into_iter()
+        // trim None value
+        .filter_map(|(k, v)| v.map(|v| (Cow::from(k), percent_encode_cow(v, targets))))
+        .collect()
+}
+
+fn generate_signature_plaintext(consumer_secret: &str, token_secret: Option<&str>) -> String {
+    format!("{}&{}", consumer_secret, token_secret.unwrap_or(""))
+}
+
+fn generate_base_string<'a>(
+    http_method: &str,
+    endpoint: &str,
+    encoded_params: &[(Cow<'a, str>, Cow<'a, str>)],
+    targets: &'static AsciiSet,
+) -> String {
+    // preprocess parameters
+    let http_method = http_method.to_ascii_uppercase();
+    let encoded_params = encoded_params
+        .iter()
+        .filter(|(k, _)| k != OAUTH_PARAM_KEY_REALM)
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<String>>()
+        .join("&");
+    // encode parameters
+    // (get/post parameters should be encoded twice.)
+    let params = percent_encode(&encoded_params, targets);
+    let http_method = percent_encode(&http_method, targets);
+    let endpoint = percent_encode(endpoint, targets);
+    // join contents to sign
+    format!("{}&{}&{}", http_method, endpoint, params)
+}
+
+fn generate_signature_hmacsha1(
+    consumer_secret: &str,
+    token_secret: Option<&str>,
+    base_str: &str,
+    targets: &'static AsciiSet,
+) -> String {
+    // prepare sign key -------------------------------------------------------
+    // preprocess sign key parameters
+    let token_secret = token_secret.unwrap_or("");
+    // encode sign key
+    let consumer_secret = percent_encode(consumer_secret, targets);
+    let token_secret = percent_encode(token_secret, targets);
+    // join keys to sign
+    let sign_key = Secret::from(format!("{}&{}", consumer_secret, token_secret));
+
+    // generate signature -----------------------------------------------------
+    // NOTE: HmacSha1 never fails, so I use `unwrap` here.
+    let mut mac = HmacSha1::new_varkey(sign_key.expose().as_bytes()).unwrap();
+    mac.input(base_str.as_bytes());
+    let hash = mac.result().code();
+    base64::encode(&hash)
+}
+
+// https://tools.ietf.org/html/rfc5849#section-3.4.3
+fn generate_signature_rsasha1(private_key_pem: &str, base_str: &str) -> Result<String, SignError> {
+    let key = rsa::RsaPrivateKey::from_pkcs8_pem(private_key_pem)
+        .or_else(|_| rsa::RsaPrivateKey::from_pkcs1_pem(private_key_pem))
+        .map_err(|_| SignError::InvalidRsaKey)?;
+    let padding = rsa::Pkcs1v15Sign {
+        hash_len: Some(20),
+        prefix: SHA1_DIGEST_INFO_PREFIX.into(),
+    };
+    let hash = Sha1::digest(base_str.as_bytes());
+    key.sign(padding, &hash)
+        .map(|signature| base64::encode(&signature))
+        .map_err(|e| SignError::Rsa(e.to_string()))
+}
+
+fn targets_for_params(encoding: EncodingProfile) -> &'static AsciiSet {
+    match encoding {
+        EncodingProfile::Rfc3986 => TARGETS_FOR_PARAMS,
+        EncodingProfile::KeepColon => TARGETS_KEEPING_COLON,
+    }
+}
+
+fn targets_for_sign(encoding: EncodingProfile) -> &'static AsciiSet {
+    match encoding {
+        EncodingProfile::Rfc3986 => TARGETS_FOR_SIGN,
+        EncodingProfile::KeepColon => TARGETS_KEEPING_COLON,
+    }
+}
+
+fn percent_encode_cow<'a, T: Into<Cow<'a, str>>>(
+    input: T,
+    targets: &'static AsciiSet,
+) -> Cow<'a, str> {
+    match input.into() {
+        Cow::Borrowed(r) => Cow::from(percent_encode(r, targets)),
+        Cow::Owned(v) => Cow::from(percent_encode(&v, targets).to_string()),
+    }
+}
+
+pub(crate) fn percent_encode<'a>(input: &'a str, targets: &'static AsciiSet) -> PercentEncode<'a> {
+    utf8_percent_encode(input, targets)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sign_rfc5849() {
+        // https://tools.ietf.org/html/rfc5849#section-1.2
+        let mut protocol = ProtocolParameters::new("dpf43f3p2l4k3l03", "wIjqoS", 137_131_200);
+        protocol.version = OAuthVersion::None;
+        protocol.callback = Some("http://printer.example.com/ready".into());
+        let signed = sign(
+            "POST",
+            "https://photos.example.net/initiate",
+            protocol,
+            vec![("realm".into(), "photos".into())],
+            ("kd94hf93k423kf44", None),
+            (EncodingProfile::Rfc3986, EncodingProfile::Rfc3986),
+        )
+        .unwrap();
+        assert_eq!(signed.signature, "74KNZJeDHnMBp0EMJ9ZHt/XKycU=");
+        assert_eq!(
+            signed.payload.last(),
+            Some(&(Cow::from("realm"), Cow::from("photos")))
+        );
+
+        let mut protocol = ProtocolParameters::new("key", "nonce", 1);
+        protocol.signature_method = SignatureMethod::RsaSha1;
+        let signed = sign(
+            "GET",
+            "https://example.com/",
+            protocol,
+            Vec::new(),
+            ("not a key", None),
+            (EncodingProfile::Rfc3986, EncodingProfile::Rfc3986),
+        );
+        assert_eq!(signed.err(), Some(SignError::InvalidRsaKey));
+    }
+
+    #[test]
+    fn test_sign_query() {
+        // https://tools.ietf.org/html/rfc5849#section-3.4.1.1
+        let mut protocol = ProtocolParameters::new("9djdj82h48djs9d2", "7d8f3e4a", 137_131_201);
+        protocol.version = OAuthVersion::None;
+        protocol.token = Some("kkk9d7dh3k39sjv7".into());
+        let signed = sign(
+            "POST",
+            "http://example.com/request?b5=%3D%253D&a3=a&c%40=&a2=r%20b",
+            protocol,
+            vec![("c2".into(), "".into()), ("a3".into(), "2 q".into())],
+            ("j49sk3j29djd", Some("dh893hdasih9")),
+            (EncodingProfile::Rfc3986, EncodingProfile::Rfc3986),
+        )
+        .unwrap();
+        assert_eq!(
+            signed.base_string,
+            "POST&http%3A%2F%2Fexample.com%2Frequest&a2%3Dr%2520b%26a3%3D2%2520q\
+             %26a3%3Da%26b5%3D%253D%25253D%26c%2540%3D%26c2%3D%26oauth_consumer_\
+             key%3D9djdj82h48djs9d2%26oauth_nonce%3D7d8f3e4a%26oauth_signature_m\
+             ethod%3DHMAC-SHA1%26oauth_timestamp%3D137131201%26oauth_token%3Dkkk\
+             9d7dh3k39sjv7"
+        );
+        // the query stays in the URL.
+        assert!(!signed.payload.iter().any(|(k, _)| k == "b5"));
+        assert_eq!(signed.payload.len(), 7);
+
+        // bytes which are not UTF-8 are signed as they are sent.
+        let protocol = ProtocolParameters::new("9djdj82h48djs9d2", "7d8f3e4a", 137_131_201);
+        let signed = sign(
+            "GET",
+            "http://example.com/request?a=%FF+b",
+            protocol,
+            Vec::new(),
+            ("j49sk3j29djd", None),
+            (EncodingProfile::Rfc3986, EncodingProfile::Rfc3986),
+        )
+        .unwrap();
+        assert!(signed
+            .base_string
+            .starts_with("GET&http%3A%2F%2Fexample.com%2Frequest&a%3D%25FF%2520b%26"));
+    }
+}
@@ -1,128 +1,40 @@
 use crate::builder::OAuthSigner;
-use crate::parameters::OAuthParameter;
-use crate::{util, v1::*};
-use chrono::Utc;
-use hmac::{Hmac, Mac};
-use io::Read;
-use percent_encoding::utf8_percent_encode;
-use percent_encoding::PercentEncode;
-use sha1::Sha1;
-use std::{
-    borrow::Cow,
-    ffi::{OsStr, OsString},
-    fs::File,
-    io,
-    path::Path,
+use crate::parameters::{OAuthParameter, OAuthSignedContent, OAuthSignedParameter};
+use crate::secret::Secret;
+use crate::v1::rfc5849::{
+    self, percent_encode, ProtocolParameters, OAUTH_PARAM_KEY_REALM, TARGETS_FOR_PARAMS,
 };
-use uuid::Uuid;
-
-type HmacSha1 = Hmac<Sha1>;
-
-const OAUTH_PARAM_KEY_CALLBACK: &str = "oauth_callback";
-const OAUTH_PARAM_KEY_CONSUMER_KEY: &str = "oauth_consumer_key";
-const OAUTH_PARAM_KEY_NONCE: &str = "oauth_nonce";
-const OAUTH_PARAM_KEY_SIGNATURE_METHOD: &str = "oauth_signature_method";
-const OAUTH_PARAM_KEY_TIMESTAMP: &str = "oauth_timestamp";
-const OAUTH_PARAM_KEY_TOKEN: &str = "oauth_nonce";
-const OAUTH_PARAM_KEY_VERSION: &str = "oauth_version";
-
-// https://tools.ietf.org/html/rfc5849#section-3.6
-// * ALPHA, DIGIT, '-', '.', '_', '~' MUST NOT be encoded.
-// * All other characters MUST be encoded.
-// * The two hexadecimal characters used to represent encoded
-//   characters MUST be uppercase.
-const TARGETS_FOR_PARAMS: &AsciiSet = &percent_encoding::NON_ALPHANUMERIC
-    .remove(b'-')
-    .remove(b'.')
-    .remove(b'_')
-    .remove(b'~');
-
-// This is also used for generating signatures.
-const TARGETS_FOR_SIGN: &AsciiSet = &percent_encoding::NON_ALPHANUMERIC
-    .remove(b'-')
-    .remove(b'.')
-    .remove(b'_')
-    .remove(b'~');
-
-const TARGETS_FOR_TWITTER_SIGN: &AsciiSet = &percent_encoding::NON_ALPHANUMERIC
-    .remove(b'-')
-    .remove(b'.')
-    .remove(b'_')
-    .remove(b'~')
-    .remove(b':'); // 🤔🤔🤔🤔🤔🤔🤔🤔🤔??????????
-
-pub enum EncodedParameter<'a> {
-    StringValue(Cow<'a, str>),
-    FileValue(Cow<'a, str>, io::Result<String>),
-}
-
-impl<'a> EncodedParameter<'a> {
-    fn read_file_as_encoded_bytes(path: &str) -> io::Result<String> {
-        let mut f = File::open(path)?;
-        let mut buf = Vec::new();
-        f.read_to_end(&mut buf)?;
-        Ok(base64::encode(&buf))
-    }
-
-    pub fn get_str(self) -> io::Result<Cow<'a, str>> {
-        match self {
-            EncodedParameter::StringValue(s) => Ok(s),
-            EncodedParameter::FileValue(k, r) => {
-                r.map(|v| Cow::Owned(EncodedParameter::format_multipart_content(k, v)))
-            }
-        }
-    }
+use crate::v1::*;
+use io::Read;
+use percent_encoding::percent_decode_str;
+use sha1::{Digest, Sha1};
+use std::{borrow::Cow, future::Future, io, path::PathBuf};
 
-    pub fn to_string(self) -> io::Result<String> {
-        match self {
-            EncodedParameter::StringValue(s) => Ok(s.to_string()),
-            EncodedParameter::FileValue(k, r) => {
-                r.map(|v| EncodedParameter::format_multipart_content(k, v))
-            }
-        }
-    }
+const AUTHORIZATION_HEADER: &str = "Authorization";
 
-    fn format_multipart_content<'b>(key: Cow<'b, str>, content: String) -> String {
-        // TODO: format for multipart content
-        content
-    }
-}
+const OAUTH_PARAM_KEY_BODY_HASH: &str = "oauth_body_hash";
+const OAUTH_PARAM_KEY_SIGNATURE: &str = "oauth_signature";
+const OAUTH_PARAM_KEY_XOAUTH_REQUESTOR_ID: &str = "xoauth_requestor_id";
+/// `oauth_callback` of clients which cannot receive callbacks (out-of-band).
+pub const OAUTH_VALUE_CALLBACK_OOB: &str = "oob";
 
-impl<'a> From<OAuthParameter<'a>> for EncodedParameter<'a> {
-    fn from(p: OAuthParameter<'a>) -> Self {
-        match p {
-            OAuthParameter::StringValue(s) => EncodedParameter::StringValue(percent_encode_cow(s)),
-            OAuthParameter::IntValue(n) => {
-                EncodedParameter::StringValue(percent_encode_cow(n.to_string()))
-            }
-            OAuthParameter::FloatValue(n) => {
-                EncodedParameter::StringValue(percent_encode_cow(n.to_string()))
-            }
-            OAuthParameter::ByteValue(b) => {
-                EncodedParameter::StringValue(percent_encode_cow(base64::encode(&b)))
-            }
-            OAuthParameter::NamedByteValue(n, b) => EncodedParameter::FileValue(
-                percent_encode_cow(n),
-                Ok(percent_encode_str(base64::encode(&b))),
-            ),
-            OAuthParameter::FileValue(path) => {
-                let file_bytes = EncodedParameter::read_file_as_encoded_bytes(&path);
-                // acquire reference to str
-                let os_path = match path {
-                    Cow::Borrowed(r) => Cow::from(OsStr::new(r)),
-                    Cow::Owned(s) => Cow::from(OsString::from(s)),
-                };
-                let filename = percent_encode_cow(
-                    Path::new(&os_path)
-                        .file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or(&"")
-                        .to_string(),
-                );
-                EncodedParameter::FileValue(filename, file_bytes)
-            }
+/// Texts of `p` as they are signed, before percent-encoding, one for each
+/// value of a `MultiValue`. File parts are never signed.
+fn text_values(p: OAuthParameter<'_>) -> Vec<Cow<'_, str>> {
+    let text = match p {
+        OAuthParameter::StringValue(s) => s,
+        OAuthParameter::IntValue(n) => n.to_string().into(),
+        OAuthParameter::UIntValue(n) => n.to_string().into(),
+        OAuthParameter::FloatValue(n) => n.to_string().into(),
+        OAuthParameter::DecimalValue(n, places) => format!("{:.*}", places, n).into(),
+        OAuthParameter::BoolValue(b, format) => Cow::Borrowed(format.format(b)),
+        OAuthParameter::ByteValue(b) => base64::encode(&b).into(),
+        OAuthParameter::NamedByteValue(..) | OAuthParameter::FileValue(_) => return Vec::new(),
+        OAuthParameter::MultiValue(values) => {
+            return values.into_iter().flat_map(text_values).collect();
         }
-    }
+    };
+    vec![text]
 }
 
 /// Contents signed with OAuth1a.
@@ -131,8 +43,112 @@ pub struct SignedContent<'a> {
     pub nonce: Cow<'a, str>,
     pub payload: Vec<(Cow<'a, str>, Cow<'a, str>)>,
     pub timestamp: i64,
+    /// Signature base string (https://tools.ietf.org/html/rfc5849#section-3.4.1).
+    pub base_string: String,
+    /// Parameters of a `multipart/form-data` body, which are not signed
+    /// (RFC 5849 section 3.4.1.3.1) and so are not in `payload`. The signed
+    /// parameters which are not put in the header then go in the query.
+    pub parts: Vec<(Cow<'a, str>, OAuthSignedParameter)>,
 }
 
+impl<'a> SignedContent<'a> {
+    /// Value of the `Authorization` header.
+    ///
+    /// Only `realm` and the `oauth_` prefixed parameters are put in the header;
+    /// the others have to be sent in the query or the body.
+    pub fn authorization_header(&self) -> String {
+        let params = self
+            .payload
+            .iter()
+            .filter(|(k, _)| k == OAUTH_PARAM_KEY_REALM || k.starts_with("oauth_"))
+            .map(|(k, v)| format!("{}=\"{}\"", k, v))
+            .chain(std::iter::once(format!(
+                "{}=\"{}\"",
+                OAUTH_PARAM_KEY_SIGNATURE,
+                percent_encode(&self.signature, TARGETS_FOR_PARAMS)
+            )))
+            .collect::<Vec<String>>()
+            .join(", ");
+        format!("{} {}", OAUTH_HEADER, params)
+    }
+
+    /// Parameters which are not put in the header, as an
+    /// `application/x-www-form-urlencoded` body.
+    pub fn form_body(&self) -> String {
+        self.payload
+            .iter()
+            .filter(|(k, _)| k != OAUTH_PARAM_KEY_REALM && !k.starts_with("oauth_"))
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<String>>()
+            .join("&")
+    }
+
+    /// Every parameter and the signature as a query string,
+    /// for requests which carry the protocol parameters in the URL.
+    pub fn query_string(&self) -> String {
+        self.payload
+            .iter()
+            .filter(|(k, _)| k != OAUTH_PARAM_KEY_REALM)
+            .map(|(k, v)| format!("{}={}", k, v))
+            .chain(std::iter::once(format!(
+                "{}={}",
+                OAUTH_PARAM_KEY_SIGNATURE,
+                percent_encode(&self.signature, TARGETS_FOR_PARAMS)
+            )))
+            .collect::<Vec<String>>()
+            .join("&")
+    }
+}
+
+impl From<SignedContent<'_>> for OAuthSignedContent {
+    fn from(signed: SignedContent<'_>) -> Self {
+        let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+        let authorization = signed.authorization_header();
+        let (protocol, others): (Vec<_>, Vec<_>) = signed
+            .payload
+            .iter()
+            .map(|(k, v)| (decode(k), decode(v)))
+            .partition(|(k, _)| k == OAUTH_PARAM_KEY_REALM || k.starts_with("oauth_"));
+        let mut protocol_parameters = protocol;
+        protocol_parameters.push((
+            OAUTH_PARAM_KEY_SIGNATURE.to_string(),
+            signed.signature.clone(),
+        ));
+        // a multipart body is not signed: what is signed goes in the query.
+        let (query, others) = if signed.parts.is_empty() {
+            (Vec::new(), others)
+        } else {
+            (others, Vec::new())
+        };
+        let mut parameters = others
+            .into_iter()
+            .map(|(k, v)| (k, OAuthSignedParameter::StringValue(v)))
+            .collect::<Vec<_>>();
+        parameters.extend(
+            signed
+                .parts
+                .into_iter()
+                .map(|(key, value)| (key.into_owned(), value)),
+        );
+        OAuthSignedContent {
+            protocol_parameters,
+            headers: vec![(AUTHORIZATION_HEADER, authorization)],
+            parameters,
+            query,
+            signature: Some(signed.signature),
+            boundary: None,
+        }
+    }
+}
+
+/// Signer of one request.
+///
+/// The query of the endpoint is signed along with the parameters, unless a
+/// file parameter makes them a `multipart/form-data` body, which is not signed.
+///
+/// The nonce generator and the clock are set once on a template signer,
+/// which is then reused with `for_request`.
+#[derive(Clone)]
 pub struct Signer<'a, T> {
     token: T,
     consumer_key: Cow<'a, str>,
@@ -142,6 +158,16 @@ pub struct Signer<'a, T> {
     signature_method: SignatureMethod,
     timestamp: Option<i64>,
     version: OAuthVersion<'a>,
+    realm: Option<Cow<'a, str>>,
+    callback: Option<Cow<'a, str>>,
+    verifier: Option<Cow<'a, str>>,
+    empty_token: bool,
+    requestor_id: Option<Cow<'a, str>>,
+    body_hash: Option<String>,
+    param_encoding: EncodingProfile,
+    base_string_encoding: EncodingProfile,
+    nonce_generator: &'a dyn NonceGenerator,
+    clock: &'a dyn Clock,
 }
 
 impl<'a> Signer<'a, ()> {
@@ -164,10 +190,52 @@ impl<'a> Signer<'a, ()> {
             nonce: None,
             timestamp: None,
             version: OAuthVersion::Default,
+            realm: None,
+            callback: None,
+            verifier: None,
+            empty_token: false,
+            requestor_id: None,
+            body_hash: None,
+            param_encoding: EncodingProfile::Rfc3986,
+            base_string_encoding: EncodingProfile::Rfc3986,
+            nonce_generator: &RandomNonce,
+            clock: &SystemClock,
         }
     }
 }
 
+impl<'a> Signer<'a, ()> {
+    /// `oauth_callback` of a temporary credential request: the URI the
+    /// resource owner is redirected to, or `OAUTH_VALUE_CALLBACK_OOB`.
+    pub fn callback<TCallback: Into<Cow<'a, str>>>(&mut self, callback: TCallback) -> &mut Self {
+        self.callback = Some(callback.into());
+        self
+    }
+
+    /// Out-of-band `oauth_callback`; the provider shows the verifier to the user.
+    pub fn callback_oob(&mut self) -> &mut Self {
+        self.callback(OAUTH_VALUE_CALLBACK_OOB)
+    }
+
+    /// Two-legged requests: send an empty `oauth_token`, which some
+    /// providers require in place of an absent one.
+    pub fn empty_token(&mut self) -> &mut Self {
+        self.empty_token = true;
+        self
+    }
+
+    /// Two-legged requests made on behalf of the user `requestor_id`
+    /// (`xoauth_requestor_id`), sent with the other non-protocol parameters,
+    /// or in the query along with a multipart body.
+    pub fn requestor_id<TRequestorId: Into<Cow<'a, str>>>(
+        &mut self,
+        requestor_id: TRequestorId,
+    ) -> &mut Self {
+        self.requestor_id = Some(requestor_id.into());
+        self
+    }
+}
+
 impl<'a> Signer<'a, Cow<'a, str>> {
     pub fn new<TConsumerKey, TEndpoint, THttpMethod, TToken>(
         consumer_key: TConsumerKey,
@@ -190,12 +258,194 @@ impl<'a> Signer<'a, Cow<'a, str>> {
             nonce: None,
             timestamp: None,
             version: OAuthVersion::Default,
+            realm: None,
+            callback: None,
+            verifier: None,
+            empty_token: false,
+            requestor_id: None,
+            body_hash: None,
+            param_encoding: EncodingProfile::Rfc3986,
+            base_string_encoding: EncodingProfile::Rfc3986,
+            nonce_generator: &RandomNonce,
+            clock: &SystemClock,
+        }
+    }
+}
+
+impl<'a> Signer<'a, Cow<'a, str>> {
+    /// `oauth_verifier` of a token request, received with the callback or from the user.
+    pub fn verifier<TVerifier: Into<Cow<'a, str>>>(&mut self, verifier: TVerifier) -> &mut Self {
+        self.verifier = Some(verifier.into());
+        self
+    }
+}
+
+impl<'a, T> Signer<'a, T> {
+    pub fn nonce<TNonce: Into<Cow<'a, str>>>(&mut self, nonce: TNonce) -> &mut Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    pub fn timestamp(&mut self, timestamp: i64) -> &mut Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn signature_method(&mut self, signature_method: SignatureMethod) -> &mut Self {
+        self.signature_method = signature_method;
+        self
+    }
+
+    pub fn version(&mut self, version: OAuthVersion<'a>) -> &mut Self {
+        self.version = version;
+        self
+    }
+
+    /// `realm` announced in the `Authorization` header; it is not signed.
+    pub fn realm<TRealm: Into<Cow<'a, str>>>(&mut self, realm: TRealm) -> &mut Self {
+        self.realm = Some(realm.into());
+        self
+    }
+
+    /// Encoding of the parameters, before they are joined.
+    pub fn param_encoding(&mut self, encoding: EncodingProfile) -> &mut Self {
+        self.param_encoding = encoding;
+        self
+    }
+
+    /// Encoding of the base string components and of the signing key.
+    pub fn base_string_encoding(&mut self, encoding: EncodingProfile) -> &mut Self {
+        self.base_string_encoding = encoding;
+        self
+    }
+
+    /// Generator of the nonces not given with `nonce`.
+    pub fn nonce_generator(&mut self, nonce_generator: &'a dyn NonceGenerator) -> &mut Self {
+        self.nonce_generator = nonce_generator;
+        self
+    }
+
+    /// Clock giving the timestamps not given with `timestamp`.
+    pub fn clock(&mut self, clock: &'a dyn Clock) -> &mut Self {
+        self.clock = clock;
+        self
+    }
+
+    fn nonce_or_generate(&mut self) -> Cow<'a, str> {
+        let nonce_generator = self.nonce_generator;
+        self.nonce
+            .take()
+            .unwrap_or_else(|| Cow::from(nonce_generator.generate()))
+    }
+
+    /// Sign a request body which is not form-encoded with its `oauth_body_hash`.
+    ///
+    /// https://oauth.googlecode.com/svn/spec/ext/body_hash/1.0/oauth-bodyhash.html
+    pub fn body_hash(&mut self, body: &[u8]) -> &mut Self {
+        self.body_hash = Some(base64::encode(&Sha1::digest(body)));
+        self
+    }
+
+    /// `body_hash` of a body read from `body` chunk by chunk, e.g. a `Multipart`
+    /// too large to be held in memory.
+    ///
+    /// For the body sent to match, hash the `multipart()` of an
+    /// `OAuthSignedContent` holding the parameters to sign, and set the
+    /// `boundary` of the signed content to that of the body hashed.
+    pub fn body_hash_from_reader<R: Read>(&mut self, mut body: R) -> io::Result<&mut Self> {
+        let mut hasher = Sha1::new();
+        let mut buf = [0u8; 8192];
+        loop {
+            match body.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => hasher.input(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        self.body_hash = Some(base64::encode(&hasher.result()));
+        Ok(self)
+    }
+
+    /// Sign once the `FileValue` parameters are loaded into byte parts with `read`.
+    ///
+    /// Signing itself does no IO, since file parts are not signed and are read
+    /// from their paths as the body is sent. This is for bodies which have to
+    /// be held in memory; `read` may use the async IO of any runtime, e.g.
+    /// `tokio::fs::read`.
+    pub async fn sign_async<TSecrets, F, Fut>(
+        self,
+        param: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
+        secrets: &TSecrets,
+        mut read: F,
+    ) -> io::Result<SignedContent<'a>>
+    where
+        Self: OAuthSigner<'a, TSecrets, io::Result<SignedContent<'a>>>,
+        F: FnMut(PathBuf) -> Fut,
+        Fut: Future<Output = io::Result<Vec<u8>>>,
+    {
+        let mut loaded = Vec::with_capacity(param.len());
+        for (key, value) in param {
+            loaded.push((key, value.load_file(&mut read).await?));
+        }
+        self.sign(loaded, secrets)
+    }
+
+    /// `realm`, `oauth_body_hash` and `xoauth_requestor_id`, which are signed
+    /// even when the other parameters are multipart parts.
+    fn extra_params(&mut self) -> Vec<(Cow<'a, str>, Cow<'a, str>)> {
+        let mut extra = Vec::new();
+        if let Some(realm) = self.realm.take() {
+            extra.push((OAUTH_PARAM_KEY_REALM.into(), realm));
+        }
+        if let Some(body_hash) = self.body_hash.take() {
+            extra.push((OAUTH_PARAM_KEY_BODY_HASH.into(), body_hash.into()));
+        }
+        if let Some(requestor_id) = self.requestor_id.take() {
+            extra.push((OAUTH_PARAM_KEY_XOAUTH_REQUESTOR_ID.into(), requestor_id));
+        }
+        extra
+    }
+
+    fn timestamp_or_now(&self) -> i64 {
+        self.timestamp.unwrap_or_else(|| self.clock.now())
+    }
+}
+
+impl<'a, T: Clone> Signer<'a, T> {
+    /// Copy of this signer for another request.
+    ///
+    /// Only the settings of this signer are copied: the nonce, the timestamp,
+    /// the callback, the verifier, the requestor id and the body hash belong to
+    /// the request they were given for, and are not.
+    pub fn for_request<TEndpoint, THttpMethod>(
+        &self,
+        endpoint: TEndpoint,
+        http_method: THttpMethod,
+    ) -> Self
+    where
+        TEndpoint: Into<Cow<'a, str>>,
+        THttpMethod: Into<Cow<'a, str>>,
+    {
+        Signer {
+            endpoint: endpoint.into(),
+            http_method: http_method.into(),
+            nonce: None,
+            timestamp: None,
+            callback: None,
+            verifier: None,
+            requestor_id: None,
+            body_hash: None,
+            ..self.clone()
         }
     }
 }
+
+/// Consumer secret and, with `T = Secret`, the token secret.
+#[derive(Clone, Debug)]
 pub struct Secrets<'a, T> {
     token_secret: T,
-    consumer_secret: Cow<'a, str>,
+    consumer_secret: Secret<'a>,
 }
 
 impl<'a> Secrets<'a, ()> {
@@ -205,12 +455,12 @@ impl<'a> Secrets<'a, ()> {
     {
         Secrets {
             token_secret: (),
-            consumer_secret: consumer_secret.into(),
+            consumer_secret: Secret::new(consumer_secret),
         }
     }
 }
 
-impl<'a> Secrets<'a, Cow<'a, str>> {
+impl<'a> Secrets<'a, Secret<'a>> {
     pub fn new<TConsumerSecret, TTokenSecret>(
         consumer_secret: TConsumerSecret,
         token_secret: TTokenSecret,
@@ -220,218 +470,164 @@ impl<'a> Secrets<'a, Cow<'a, str>> {
         TTokenSecret: Into<Cow<'a, str>>,
     {
         Secrets {
-            token_secret: token_secret.into(),
-            consumer_secret: consumer_secret.into(),
+            token_secret: Secret::new(token_secret),
+            consumer_secret: Secret::new(consumer_secret),
         }
     }
 }
 
 impl<'a> OAuthSigner<'a, Secrets<'a, ()>, io::Result<SignedContent<'a>>> for Signer<'a, ()> {
     fn sign(
-        self,
+        mut self,
         param: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
         secrets: &Secrets<'a, ()>,
     ) -> io::Result<SignedContent<'a>> {
+        let nonce = self.nonce_or_generate();
+        let timestamp = self.timestamp_or_now();
+        let extra = self.extra_params();
+        let empty_token = if self.empty_token {
+            Some((Cow::from(""), ""))
+        } else {
+            None
+        };
         sign_oauthv1(
             self.endpoint,
             self.http_method,
-            (self.consumer_key, &secrets.consumer_secret),
-            None,
+            (self.consumer_key, secrets.consumer_secret.expose()),
+            empty_token,
             self.signature_method,
-            self.nonce,
+            nonce,
             self.version,
-            self.timestamp,
+            timestamp,
+            (self.callback, self.verifier),
+            extra,
             param,
+            (self.param_encoding, self.base_string_encoding),
         )
     }
 }
 
-impl<'a> OAuthSigner<'a, Secrets<'a, Cow<'a, str>>, io::Result<SignedContent<'a>>>
+impl<'a> OAuthSigner<'a, Secrets<'a, Secret<'a>>, io::Result<SignedContent<'a>>>
     for Signer<'a, Cow<'a, str>>
 {
     fn sign(
-        self,
+        mut self,
         param: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
-        secrets: &Secrets<'a, Cow<'a, str>>,
+        secrets: &Secrets<'a, Secret<'a>>,
     ) -> io::Result<SignedContent<'a>> {
+        let nonce = self.nonce_or_generate();
+        let timestamp = self.timestamp_or_now();
+        let extra = self.extra_params();
         sign_oauthv1(
             self.endpoint,
             self.http_method,
-            (self.consumer_key, &secrets.consumer_secret),
-            Some((self.token, &secrets.token_secret)),
+            (self.consumer_key, secrets.consumer_secret.expose()),
+            Some((self.token, secrets.token_secret.expose())),
             self.signature_method,
-            self.nonce,
+            nonce,
             self.version,
-            self.timestamp,
+            timestamp,
+            (self.callback, self.verifier),
+            extra,
             param,
+            (self.param_encoding, self.base_string_encoding),
         )
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn sign_oauthv1<'a>(
     endpoint: Cow<'a, str>,
     http_method: Cow<'a, str>,
     consumer_key_and_secret: (Cow<'a, str>, &str),
     token_and_secret: Option<(Cow<'a, str>, &str)>,
     signature_method: SignatureMethod,
-    nonce: Option<Cow<'a, str>>,
+    nonce: Cow<'a, str>,
     version: OAuthVersion<'a>,
-    timestamp: Option<i64>,
+    timestamp: i64,
+    callback_and_verifier: (Option<Cow<'a, str>>, Option<Cow<'a, str>>),
+    extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
     parameters: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
+    encodings: (EncodingProfile, EncodingProfile),
 ) -> io::Result<SignedContent<'a>> {
     // destructure & setup variables
     let (c_key, c_secret) = consumer_key_and_secret;
     let (token, token_secret) = token_and_secret
         .map(|(t, s)| (Some(t), Some(s)))
         .unwrap_or((None, None));
-    let timestamp = timestamp.unwrap_or_else(|| Utc::now().timestamp());
-    // generate nonce when it is not specified
-    let nonce = nonce.unwrap_or_else(|| Cow::from(format!("{}", Uuid::new_v4())));
+    let (callback, verifier) = callback_and_verifier;
     let sampled_nonce = nonce.clone();
 
-    // prepare parameters
-    let basic_params_encoded = build_basic_params(
-        c_key,
+    // with a file part, the parameters are a multipart/form-data body,
+    // which is not signed (RFC 5849 section 3.4.1.3.1).
+    let multipart = parameters.iter().any(|(_, v)| v.is_part());
+    let mut user_params = Vec::with_capacity(parameters.len() + extra_params.len());
+    let mut parts = Vec::new();
+    for (key, v) in parameters {
+        if multipart {
+            for v in OAuthSignedParameter::from_parameter(v) {
+                parts.push((key.clone(), v));
+            }
+        } else {
+            for v in text_values(v) {
+                user_params.push((key.clone(), v));
+            }
+        }
+    }
+    user_params.extend(extra_params);
+
+    let protocol = ProtocolParameters {
+        consumer_key: c_key,
         token,
         signature_method,
         nonce,
         timestamp,
-        version.into(),
-    );
-    let user_params_encoded = parameters
-        .into_iter()
-        .map(|(k, v)| EncodedParameter::from(v).get_str().map(|v| (k, v)))
-        .collect::<io::Result<Vec<(Cow<'a, str>, Cow<str>)>>>()?;
-    // join two paramters and sort by alphabetical order
-    let mut payload =
-        [basic_params_encoded, user_params_encoded].concat::<(Cow<'a, str>, Cow<str>)>();
-    payload.sort();
-
-    let signature = match signature_method {
-        SignatureMethod::PlainText => generate_signature_plaintext(c_secret, token_secret),
-        SignatureMethod::HmacSha1 => {
-            generate_signature_hmacsha1(c_secret, token_secret, &http_method, &endpoint, &payload)
-        }
+        version,
+        callback,
+        verifier,
     };
+    let signed = rfc5849::sign(
+        &http_method,
+        &endpoint,
+        protocol,
+        user_params,
+        (c_secret, token_secret),
+        encodings,
+    )?;
     Ok(SignedContent {
-        signature,
+        signature: signed.signature,
         nonce: sampled_nonce,
-        payload,
+        payload: signed.payload,
+        parts,
         timestamp,
+        base_string: signed.base_string,
     })
 }
 
-fn build_basic_params<'a>(
-    consumer_key: Cow<'a, str>,
-    token: Option<Cow<'a, str>>,
-    signature_method: SignatureMethod,
-    nonce: Cow<'a, str>,
-    timestamp: i64,
-    version: Option<Cow<'a, str>>,
-) -> Vec<(Cow<'a, str>, Cow<'a, str>)> {
-    // build authorization basic parameters
-    let params = vec![
-        // owned parameters
-        (
-            OAUTH_PARAM_KEY_TIMESTAMP,
-            Some(Cow::Owned(format!("{}", timestamp))),
-        ),
-        // borrowed parameters
-        (OAUTH_PARAM_KEY_CONSUMER_KEY, Some(consumer_key)),
-        (
-            OAUTH_PARAM_KEY_SIGNATURE_METHOD,
-            Some(Cow::Borrowed(signature_method.into())),
-        ),
-        (OAUTH_PARAM_KEY_NONCE, Some(nonce)),
-        // noneable borrowed parameters
-        (OAUTH_PARAM_KEY_VERSION, version),
-        (OAUTH_PARAM_KEY_TOKEN, token),
-    ];
-
-    return params
-        .into_iter()
-        // trim None value
-        .filter_map(|(k, v)| v.map(|v| (Cow::from(k), percent_encode_cow(v))))
-        .collect();
-}
-
-fn generate_signature_plaintext<'a>(consumer_secret: &str, token_secret: Option<&str>) -> String {
-    format!("{}&{}", consumer_secret, token_secret.unwrap_or(""))
-}
-
-fn generate_signature_hmacsha1<'a>(
-    consumer_secret: &str,
-    token_secret: Option<&str>,
-    http_method: &str,
-    endpoint: &str,
-    encoded_params: &Vec<(Cow<'a, str>, Cow<'a, str>)>,
-) -> String {
-    // prepare contents to sign -----------------------------------------------
-    // preprocess parameters
-    let http_method = http_method.to_ascii_uppercase();
-    let encoded_params = encoded_params
-        .into_iter()
-        .filter(|(k, _)| k != "realm")
-        .map(|(k, v)| format!("{}={}", k, v))
-        .collect::<Vec<String>>()
-        .join("&");
-    // encode parameters
-    // (get/post parameters should be encoded twice.)
-    let params = percent_encode(&encoded_params);
-    let http_method = percent_encode(&http_method);
-    let endpoint = percent_encode(endpoint);
-    // join contents to sign
-    let base_str = format!("{}&{}&{}", http_method, endpoint, params);
-
-    // prepare sign key -------------------------------------------------------
-    // preprocess sign key parameters
-    let token_secret = token_secret.unwrap_or("");
-    // encode sign key
-    let consumer_secret = percent_encode(consumer_secret);
-    let token_secret = percent_encode(token_secret);
-    // join keys to sign
-    let sign_key = format!("{}&{}", consumer_secret, token_secret);
-
-    // generate signature -----------------------------------------------------
-    // NOTE: HmacSha1 never fails, so I use `unwrap` here.
-    let mut mac = HmacSha1::new_varkey(sign_key.as_bytes()).unwrap();
-    mac.input(base_str.as_bytes());
-    let hash = mac.result().code();
-    return base64::encode(&hash);
-}
-
-fn percent_encode_str<'a, T: Into<Cow<'a, str>>>(input: T) -> String {
-    percent_encode(&(input.into())).to_string()
-}
-
-fn percent_encode_cow<'a, T: Into<Cow<'a, str>>>(input: T) -> Cow<'a, str> {
-    match input.into() {
-        Cow::Borrowed(r) => Cow::from(percent_encode(r)),
-        Cow::Owned(v) => Cow::from(percent_encode(&v).to_string()),
-    }
-}
-
-fn percent_encode<'a>(input: &'a str) -> PercentEncode<'a> {
-    utf8_percent_encode(input, TARGETS_FOR_PARAMS)
-}
-
+#[cfg(test)]
 mod test {
     use super::*;
     use crate::util;
+    use crate::v1::rfc5849::SHA1_DIGEST_INFO_PREFIX;
     use crate::v1::SignatureMethod::HmacSha1;
+    use crate::BoolFormat;
+    use crate::{OAuthSignedParameter, SignedBody};
 
     #[test]
     fn test_sign_rfc5849() {
         let url = url::Url::parse("https://photos.example.net/initiate").unwrap();
-        let (endpoint, mut query) = util::url_to_endpoint_and_queries(&url);
+        let (endpoint, mut query) = util::url_to_endpoint_and_queries(&url).unwrap();
         let method = "post";
         let c_key = "dpf43f3p2l4k3l03";
         let c_secret = "kd94hf93k423kf44";
         let nonce = "wIjqoS";
         let timestamp: i64 = 137_131_200;
         // setup query
-        query.push(("realm", "photos"));
-        query.push(("oauth_callback", "http://printer.example.com/ready"));
+        query.push(("realm".into(), "photos".into()));
+        query.push((
+            "oauth_callback".into(),
+            "http://printer.example.com/ready".into(),
+        ));
         let query = query;
 
         let sign = sign_oauthv1(
@@ -440,16 +636,630 @@ mod test {
             (c_key.into(), c_secret),
             None,
             HmacSha1,
-            Some(nonce.into()),
+            nonce.into(),
             OAuthVersion::None,
-            Some(timestamp),
+            timestamp,
+            (None, None),
+            Vec::new(),
             query
                 .into_iter()
-                .map(|(k, v)| (Cow::from(k), OAuthParameter::from(v)))
+                .map(|(k, v)| (k, OAuthParameter::StringValue(v)))
                 .collect(),
+            (EncodingProfile::Rfc3986, EncodingProfile::Rfc3986),
         )
         .unwrap();
-        println!("{:#?}", sign.signature);
         assert_eq!("74KNZJeDHnMBp0EMJ9ZHt/XKycU=", sign.signature);
     }
+
+    #[test]
+    fn test_callback_and_verifier() {
+        // https://tools.ietf.org/html/rfc5849#section-1.2
+        let mut signer = Signer::<()>::new(
+            "dpf43f3p2l4k3l03",
+            "https://photos.example.net/initiate",
+            "post",
+        );
+        signer
+            .nonce("wIjqoS")
+            .timestamp(137_131_200)
+            .version(OAuthVersion::None)
+            .realm("photos")
+            .callback("http://printer.example.com/ready");
+        let signed = signer
+            .sign(vec![], &Secrets::<()>::new("kd94hf93k423kf44"))
+            .unwrap();
+        assert_eq!("74KNZJeDHnMBp0EMJ9ZHt/XKycU=", signed.signature);
+        assert!(signed
+            .authorization_header()
+            .contains("oauth_callback=\"http%3A%2F%2Fprinter.example.com%2Fready\""));
+        assert_eq!(signed.form_body(), "");
+
+        let mut signer = Signer::<()>::new(
+            "dpf43f3p2l4k3l03",
+            "https://photos.example.net/initiate",
+            "POST",
+        );
+        signer.callback_oob();
+        let signed = signer
+            .sign(vec![], &Secrets::<()>::new("kd94hf93k423kf44"))
+            .unwrap();
+        assert!(signed
+            .authorization_header()
+            .contains("oauth_callback=\"oob\""));
+
+        let mut signer = Signer::<Cow<str>>::new(
+            "dpf43f3p2l4k3l03",
+            "https://photos.example.net/token",
+            "POST",
+            "hh5s93j4hdidpola",
+        );
+        signer.verifier("hfdp7dh39dks9884");
+        let signed = signer
+            .sign(
+                vec![],
+                &Secrets::<Secret>::new("kd94hf93k423kf44", "hdhd0244k9j7ao03"),
+            )
+            .unwrap();
+        assert!(signed
+            .authorization_header()
+            .contains("oauth_verifier=\"hfdp7dh39dks9884\""));
+        assert!(signed
+            .base_string
+            .contains("oauth_verifier%3Dhfdp7dh39dks9884"));
+        assert_eq!(signed.form_body(), "");
+    }
+
+    #[test]
+    fn test_two_legged() {
+        let sign = |empty_token: bool| {
+            let mut signer = Signer::<()>::new(
+                "example.com",
+                "https://www.google.com/m8/feeds/contacts/default/full",
+                "GET",
+            );
+            signer
+                .nonce("nonce")
+                .timestamp(1_318_622_958)
+                .requestor_id("user@example.com");
+            if empty_token {
+                signer.empty_token();
+            }
+            signer.sign(vec![], &Secrets::<()>::new("secret")).unwrap()
+        };
+        let signed = sign(true);
+        assert!(signed.authorization_header().contains("oauth_token=\"\""));
+        assert!(signed.base_string.ends_with(
+            "%26oauth_token%3D%26oauth_version%3D1.0%26xoauth_requestor_id%3Duser%2540example.com"
+        ));
+        assert!(signed
+            .query_string()
+            .contains("xoauth_requestor_id=user%40example.com"));
+        assert!(!signed
+            .authorization_header()
+            .contains("xoauth_requestor_id"));
+
+        let without_token = sign(false);
+        assert!(!without_token.authorization_header().contains("oauth_token"));
+        assert_ne!(without_token.signature, signed.signature);
+    }
+
+    #[test]
+    fn test_sign_with_token() {
+        // https://developer.twitter.com/en/docs/basics/authentication/guides/creating-a-signature
+        let mut signer = Signer::<Cow<str>>::new(
+            "xvz1evFS4wEEPTGEFPHBog",
+            "https://api.twitter.com/1.1/statuses/update.json",
+            "post",
+            "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb",
+        );
+        signer
+            .nonce("kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg")
+            .timestamp(1_318_622_958);
+        let secrets = Secrets::<Secret>::new(
+            "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw",
+            "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE",
+        );
+        let sign = signer
+            .sign(
+                vec![
+                    ("include_entities".into(), "true".into()),
+                    (
+                        "status".into(),
+                        "Hello Ladies + Gentlemen, a signed OAuth request!".into(),
+                    ),
+                ],
+                &secrets,
+            )
+            .unwrap();
+        assert_eq!("hCtSmYh+iHYCEqBWrE7C7hYmtUk=", sign.signature);
+        assert!(sign.base_string.starts_with(
+            "POST&https%3A%2F%2Fapi.twitter.com%2F1.1%2Fstatuses%2Fupdate.json&include_entities%3Dtrue%26oauth_consumer_key"
+        ));
+        assert_eq!(
+            sign.authorization_header(),
+            "OAuth oauth_consumer_key=\"xvz1evFS4wEEPTGEFPHBog\", \
+             oauth_nonce=\"kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg\", \
+             oauth_signature_method=\"HMAC-SHA1\", \
+             oauth_timestamp=\"1318622958\", \
+             oauth_token=\"370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb\", \
+             oauth_version=\"1.0\", \
+             oauth_signature=\"hCtSmYh%2BiHYCEqBWrE7C7hYmtUk%3D\""
+        );
+    }
+
+    #[test]
+    fn test_normalize_parameters() {
+        // https://tools.ietf.org/html/rfc5849#section-3.4.1.1
+        let mut signer = Signer::<Cow<str>>::new(
+            "9djdj82h48djs9d2",
+            "http://example.com/request",
+            "POST",
+            "kkk9d7dh3k39sjv7",
+        );
+        signer
+            .nonce("7d8f3e4a")
+            .timestamp(137_131_201)
+            .version(OAuthVersion::None);
+        let secrets = Secrets::<Secret>::new("j49sk3j29djd", "dh893hdasih9");
+        let sign = signer
+            .sign(
+                vec![
+                    ("b5".into(), "=%3D".into()),
+                    ("a3".into(), OAuthParameter::multi(vec!["a", "2 q"])),
+                    ("c@".into(), "".into()),
+                    ("a2".into(), "r b".into()),
+                    ("c2".into(), "".into()),
+                ],
+                &secrets,
+            )
+            .unwrap();
+        assert_eq!(
+            sign.base_string,
+            "POST&http%3A%2F%2Fexample.com%2Frequest&a2%3Dr%2520b%26a3%3D2%2520q\
+             %26a3%3Da%26b5%3D%253D%25253D%26c%2540%3D%26c2%3D%26oauth_consumer_\
+             key%3D9djdj82h48djs9d2%26oauth_nonce%3D7d8f3e4a%26oauth_signature_m\
+             ethod%3DHMAC-SHA1%26oauth_timestamp%3D137131201%26oauth_token%3Dkkk\
+             9d7dh3k39sjv7"
+        );
+    }
+
+    #[test]
+    fn test_sign_parsed_query() {
+        let mut signer = Signer::<()>::new("dpf43f3p2l4k3l03", "https://api.example.com/", "GET");
+        signer
+            .nonce("wIjqoS")
+            .timestamp(137_131_200)
+            .version(OAuthVersion::None);
+        let sign = signer
+            .sign(
+                crate::parse_query("q=a+b%2Bc&flag&lang=ja").unwrap(),
+                &Secrets::<()>::new("kd94hf93k423kf44"),
+            )
+            .unwrap();
+        assert!(sign.base_string.ends_with(
+            "&flag%3D%26lang%3Dja%26oauth_consumer_key%3Ddpf43f3p2l4k3l03\
+             %26oauth_nonce%3DwIjqoS%26oauth_signature_method%3DHMAC-SHA1\
+             %26oauth_timestamp%3D137131200%26q%3Da%2520b%252Bc"
+        ));
+        assert_eq!(sign.form_body(), "q=a%20b%2Bc&flag=&lang=ja");
+    }
+
+    #[test]
+    fn test_signed_content() {
+        let mut signer = Signer::<()>::new(
+            "dpf43f3p2l4k3l03",
+            "https://photos.example.net/upload",
+            "POST",
+        );
+        signer.nonce("wIjqoS").timestamp(137_131_200);
+        let sign = signer
+            .sign(
+                vec![
+                    ("title".into(), "a+b".into()),
+                    (
+                        "photo".into(),
+                        OAuthParameter::from_bytes("a.jpg", &b"\xff\xd8"[..]),
+                    ),
+                ],
+                &Secrets::<()>::new("kd94hf93k423kf44"),
+            )
+            .unwrap();
+        let header = sign.authorization_header();
+        let signature = sign.signature.clone();
+        let signed = OAuthSignedContent::from(sign);
+        assert_eq!(signed.headers, vec![("Authorization", header)]);
+        assert_eq!(signed.signature.as_deref(), Some(signature.as_str()));
+        assert!(signed
+            .protocol_parameters
+            .contains(&("oauth_signature".to_string(), signature)));
+        assert_eq!(
+            signed.parameters,
+            vec![
+                (
+                    "title".to_string(),
+                    OAuthSignedParameter::StringValue("a+b".to_string())
+                ),
+                (
+                    "photo".to_string(),
+                    OAuthSignedParameter::ByteValue("a.jpg".to_string(), vec![0xff, 0xd8])
+                ),
+            ]
+        );
+
+        let mut url = url::Url::parse("https://photos.example.net/upload").unwrap();
+        assert!(signed.request_body("GET", &mut url).is_err());
+        let body = signed.request_body("POST", &mut url).unwrap().unwrap();
+        assert!(body
+            .content_type()
+            .starts_with("multipart/form-data; boundary="));
+        let mut multipart = match body {
+            SignedBody::Multipart(multipart) => multipart,
+            SignedBody::Form(_) => panic!("expected a multipart body"),
+        };
+        let mut bytes = Vec::new();
+        multipart.reader_by_ref().read_to_end(&mut bytes).unwrap();
+        assert!(bytes.windows(2).any(|w| w == [0xff, 0xd8]));
+        assert_eq!(multipart.content_length().unwrap(), bytes.len() as u64);
+        assert_eq!(url.query(), None);
+    }
+
+    #[test]
+    fn test_sign_multipart() {
+        // multipart/form-data bodies are not signed, and files are not read to sign.
+        let mut signer = Signer::<()>::new(
+            "dpf43f3p2l4k3l03",
+            "https://photos.example.net/upload?album=1",
+            "POST",
+        );
+        signer.nonce("wIjqoS").timestamp(137_131_200);
+        let secrets = Secrets::<()>::new("kd94hf93k423kf44");
+        let multipart = signer
+            .clone()
+            .sign(
+                vec![
+                    ("title".into(), "a+b".into()),
+                    (
+                        "photo".into(),
+                        OAuthParameter::from_file("/nonexistent/vacation.jpg"),
+                    ),
+                ],
+                &secrets,
+            )
+            .unwrap();
+        let no_body = signer.sign(vec![], &secrets).unwrap();
+        assert_eq!(multipart.signature, no_body.signature);
+        assert_eq!(multipart.base_string, no_body.base_string);
+        assert_eq!(multipart.payload, no_body.payload);
+        assert!(multipart.base_string.contains("album%3D1"));
+        assert_eq!(
+            multipart.parts,
+            vec![
+                (
+                    Cow::from("title"),
+                    OAuthSignedParameter::StringValue("a+b".to_string())
+                ),
+                (
+                    Cow::from("photo"),
+                    OAuthSignedParameter::FileValue("/nonexistent/vacation.jpg".into())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sign_multipart_with_requestor_id() {
+        // the requestor id is signed and sent in the query, unlike the parts.
+        let mut signer = Signer::<()>::new(
+            "dpf43f3p2l4k3l03",
+            "https://photos.example.net/upload",
+            "POST",
+        );
+        signer
+            .empty_token()
+            .requestor_id("jane@example.com")
+            .nonce("wIjqoS")
+            .timestamp(137_131_200);
+        let signed = signer
+            .sign(
+                vec![(
+                    "photo".into(),
+                    OAuthParameter::from_bytes("vacation.jpg", b"jpeg"),
+                )],
+                &Secrets::<()>::new("kd94hf93k423kf44"),
+            )
+            .unwrap();
+        assert!(signed
+            .base_string
+            .contains("xoauth_requestor_id%3Djane%2540example.com"));
+        assert!(signed
+            .parts
+            .iter()
+            .all(|(k, _)| k != OAUTH_PARAM_KEY_XOAUTH_REQUESTOR_ID));
+
+        let content = OAuthSignedContent::from(signed);
+        let mut url = url::Url::parse("https://photos.example.net/upload").unwrap();
+        let body = content.request_body("POST", &mut url).unwrap();
+        assert!(matches!(body, Some(SignedBody::Multipart(_))));
+        assert_eq!(url.query(), Some("xoauth_requestor_id=jane%40example.com"));
+        assert!(content
+            .parameters
+            .iter()
+            .all(|(k, _)| k != OAUTH_PARAM_KEY_XOAUTH_REQUESTOR_ID));
+    }
+
+    #[test]
+    fn test_typed_parameters() {
+        let mut signer = Signer::<()>::new("dpf43f3p2l4k3l03", "https://api.example.com/", "GET");
+        signer.nonce("wIjqoS").timestamp(137_131_200);
+        let sign = signer
+            .sign(
+                vec![
+                    ("a".into(), true.into()),
+                    (
+                        "b".into(),
+                        OAuthParameter::bool_as(false, BoolFormat::OneZero),
+                    ),
+                    ("c".into(), u64::MAX.into()),
+                    ("d".into(), OAuthParameter::decimal(1e21, 2)),
+                    ("e".into(), OAuthParameter::decimal(-0.125, 1)),
+                    ("f".into(), OAuthParameter::multi(vec![10u32, 9, 100])),
+                    ("g".into(), 0.1.into()),
+                ],
+                &Secrets::<()>::new("kd94hf93k423kf44"),
+            )
+            .unwrap();
+        assert_eq!(
+            sign.form_body(),
+            "a=true&b=0&c=18446744073709551615&d=1000000000000000000000.00&e=-0.1\
+             &f=10&f=9&f=100&g=0.1"
+        );
+    }
+
+    #[test]
+    fn test_encoding_profiles() {
+        let sign = |param_encoding, base_string_encoding| {
+            let mut signer = Signer::<()>::new("key", "https://api.example.com/1/time", "GET");
+            signer
+                .nonce("nonce")
+                .timestamp(1_318_622_958)
+                .version(OAuthVersion::None)
+                .param_encoding(param_encoding)
+                .base_string_encoding(base_string_encoding);
+            signer
+                .sign(
+                    vec![("at".into(), "12:30".into())],
+                    &Secrets::<()>::new("secret:1"),
+                )
+                .unwrap()
+        };
+        let strict = sign(EncodingProfile::Rfc3986, EncodingProfile::Rfc3986);
+        assert_eq!(
+            strict.base_string,
+            "GET&https%3A%2F%2Fapi.example.com%2F1%2Ftime&at%3D12%253A30\
+             %26oauth_consumer_key%3Dkey%26oauth_nonce%3Dnonce\
+             %26oauth_signature_method%3DHMAC-SHA1%26oauth_timestamp%3D1318622958"
+        );
+
+        let keep_colon_params = sign(EncodingProfile::KeepColon, EncodingProfile::Rfc3986);
+        assert!(keep_colon_params
+            .payload
+            .contains(&("at".into(), "12:30".into())));
+        assert!(keep_colon_params.base_string.contains("&at%3D12%3A30%26"));
+        assert_ne!(keep_colon_params.signature, strict.signature);
+
+        let keep_colon = sign(EncodingProfile::KeepColon, EncodingProfile::KeepColon);
+        assert!(keep_colon
+            .base_string
+            .starts_with("GET&https:%2F%2Fapi.example.com%2F1%2Ftime&at%3D12:30%26"));
+        assert_ne!(keep_colon.signature, keep_colon_params.signature);
+    }
+
+    #[test]
+    fn test_sign_rsasha1() {
+        use rsa::pkcs8::{EncodePrivateKey, LineEnding};
+        let key = rsa::RsaPrivateKey::new(&mut rand_core::OsRng, 1024).unwrap();
+        let pem = key.to_pkcs8_pem(LineEnding::LF).unwrap();
+        let mut signer = Signer::<()>::new(
+            "dpf43f3p2l4k3l03",
+            "https://photos.example.net/initiate",
+            "POST",
+        );
+        signer.signature_method(SignatureMethod::RsaSha1);
+        let signed = signer
+            .sign(vec![], &Secrets::<()>::new(pem.as_str()))
+            .unwrap();
+        assert!(signed
+            .payload
+            .contains(&("oauth_signature_method".into(), "RSA-SHA1".into())));
+
+        let padding = rsa::Pkcs1v15Sign {
+            hash_len: Some(20),
+            prefix: SHA1_DIGEST_INFO_PREFIX.into(),
+        };
+        let hash = Sha1::digest(signed.base_string.as_bytes());
+        let signature = base64::decode(&signed.signature).unwrap();
+        assert!(key
+            .to_public_key()
+            .verify(padding, &hash, &signature)
+            .is_ok());
+
+        let mut signer = Signer::<()>::new("k", "https://photos.example.net/initiate", "POST");
+        signer.signature_method(SignatureMethod::RsaSha1);
+        assert!(signer
+            .sign(vec![], &Secrets::<()>::new("kd94hf93k423kf44"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_reproducible_signer() {
+        fn sign(nonce_generator: &SeededNonce) -> Vec<(String, i64, String)> {
+            let mut template = Signer::<()>::new("dpf43f3p2l4k3l03", "", "");
+            template
+                .nonce_generator(nonce_generator)
+                .clock(&FixedClock(137_131_200));
+            let secrets = Secrets::<()>::new("kd94hf93k423kf44");
+            [
+                "https://photos.example.net/initiate",
+                "https://photos.example.net/token",
+            ]
+            .iter()
+            .map(|endpoint| {
+                let signed = template
+                    .for_request(*endpoint, "POST")
+                    .sign(vec![], &secrets)
+                    .unwrap();
+                (
+                    signed.nonce.into_owned(),
+                    signed.timestamp,
+                    signed.signature,
+                )
+            })
+            .collect()
+        }
+        let first = sign(&SeededNonce::new(7));
+        assert_eq!(first[0].1, 137_131_200);
+        assert_ne!(first[0].0, first[1].0);
+        assert_eq!(sign(&SeededNonce::new(7)), first);
+    }
+
+    #[test]
+    fn test_for_request() {
+        let mut template = Signer::<()>::new("dpf43f3p2l4k3l03", "", "");
+        template
+            .realm("Photos")
+            .callback("http://printer.example.com/ready")
+            .requestor_id("jane@example.com")
+            .body_hash(b"size=original");
+        let secrets = Secrets::<()>::new("kd94hf93k423kf44");
+        let signed = template
+            .for_request("https://photos.example.net/photos", "GET")
+            .sign(vec![], &secrets)
+            .unwrap();
+        let header = signed.authorization_header();
+        assert!(header.contains("realm=\"Photos\""));
+        for key in &["oauth_callback", "oauth_body_hash", "xoauth_requestor_id"] {
+            assert!(!header.contains(key), "{}", key);
+            assert!(!signed.base_string.contains(key), "{}", key);
+        }
+
+        let mut template = Signer::<Cow<str>>::new("dpf43f3p2l4k3l03", "", "", "hh5s93j4hdidpola");
+        template.verifier("hfdp7dh39dks9884");
+        let signed = template
+            .for_request("https://photos.example.net/photos", "GET")
+            .sign(
+                vec![],
+                &Secrets::<Secret>::new("kd94hf93k423kf44", "hdhd0244k9j7ao03"),
+            )
+            .unwrap();
+        assert!(!signed.authorization_header().contains("oauth_verifier"));
+    }
+
+    #[test]
+    fn test_body_hash_from_reader() {
+        let mut multipart = crate::multipart::Multipart::with_boundary("b");
+        multipart.reader("media", "video.mp4", io::Cursor::new(vec![7u8; 100_000]));
+        let mut body = Vec::new();
+        multipart.reader_by_ref().read_to_end(&mut body).unwrap();
+
+        let mut signer = Signer::<()>::new(
+            "dpf43f3p2l4k3l03",
+            "https://photos.example.net/upload",
+            "POST",
+        );
+        signer.nonce("wIjqoS").timestamp(137_131_200);
+        let secrets = Secrets::<()>::new("kd94hf93k423kf44");
+        let mut in_memory = signer.clone();
+        in_memory.body_hash(&body);
+        signer
+            .body_hash_from_reader(multipart.into_reader())
+            .unwrap();
+        assert_eq!(
+            signer.sign(vec![], &secrets).unwrap().payload,
+            in_memory.sign(vec![], &secrets).unwrap().payload
+        );
+    }
+
+    #[test]
+    fn test_body_hash_of_sent_multipart() {
+        let params = || {
+            vec![
+                ("status".into(), OAuthParameter::from("hi")),
+                (
+                    "media".into(),
+                    OAuthParameter::from_bytes("video.mp4", vec![7u8; 100_000]),
+                ),
+            ]
+        };
+        let mut hashed = OAuthSignedContent::default()
+            .push_parameters(params())
+            .multipart();
+        let mut signer = Signer::<()>::new(
+            "dpf43f3p2l4k3l03",
+            "https://photos.example.net/upload",
+            "POST",
+        );
+        signer
+            .body_hash_from_reader(hashed.reader_by_ref())
+            .unwrap();
+        let mut content = OAuthSignedContent::from(
+            signer
+                .sign(params(), &Secrets::<()>::new("kd94hf93k423kf44"))
+                .unwrap(),
+        );
+        content.boundary = Some(hashed.boundary().to_string());
+
+        let mut url = url::Url::parse("https://photos.example.net/upload").unwrap();
+        let mut sent = Vec::new();
+        match content.request_body("POST", &mut url).unwrap() {
+            Some(SignedBody::Multipart(multipart)) => {
+                multipart.into_reader().read_to_end(&mut sent).unwrap();
+            }
+            _ => panic!("expected a multipart body"),
+        }
+        let body_hash = content
+            .protocol_parameters
+            .iter()
+            .find(|(k, _)| k == OAUTH_PARAM_KEY_BODY_HASH)
+            .map(|(_, v)| v.as_str());
+        assert_eq!(
+            body_hash,
+            Some(base64::encode(&Sha1::digest(&sent)).as_str())
+        );
+    }
+
+    #[test]
+    fn test_sign_async() {
+        use futures_executor::block_on;
+
+        let path = std::env::temp_dir().join(format!(
+            "oauthsign-{}-test-sign-async.jpg",
+            std::process::id()
+        ));
+        std::fs::write(&path, b"\xff\xd8\xff\xe0 vacation").unwrap();
+        let path = path.to_str().unwrap().to_string();
+        let mut signer = Signer::<()>::new(
+            "dpf43f3p2l4k3l03",
+            "https://photos.example.net/upload",
+            "POST",
+        );
+        signer.nonce("wIjqoS").timestamp(137_131_200);
+        let secrets = Secrets::<()>::new("kd94hf93k423kf44");
+        let params = || vec![("photo".into(), OAuthParameter::from_file(path.as_str()))];
+
+        let blocking = signer.clone().sign(params(), &secrets).unwrap();
+        let loaded = block_on(
+            signer
+                .clone()
+                .sign_async(params(), &secrets, |p| std::future::ready(std::fs::read(p))),
+        )
+        .unwrap();
+        assert_eq!(loaded.signature, blocking.signature);
+        assert_eq!(loaded.payload, blocking.payload);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(block_on(signer.sign_async(params(), &secrets, |p| {
+            std::future::ready(std::fs::read(p))
+        }))
+        .is_err());
+    }
 }
@@ -0,0 +1,64 @@
+use crate::secret::Secret;
+use url::form_urlencoded;
+
+const OAUTH_PARAM_KEY_TOKEN: &str = "oauth_token";
+const OAUTH_PARAM_KEY_TOKEN_SECRET: &str = "oauth_token_secret";
+
+/// Credentials issued by the temporary credential or token endpoints.
+///
+/// https://tools.ietf.org/html/rfc5849#section-2.3
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenResponse {
+    pub token: String,
+    pub token_secret: Secret<'static>,
+    /// Other parameters of the response, e.g. `oauth_callback_confirmed` or `user_id`.
+    pub extra: Vec<(String, String)>,
+}
+
+impl TokenResponse {
+    /// Parse an `application/x-www-form-urlencoded` response body,
+    /// `None` if either `oauth_token` or `oauth_token_secret` is missing.
+    pub fn from_slice(body: &[u8]) -> Option<Self> {
+        let mut token = None;
+        let mut token_secret = None;
+        let mut extra = Vec::new();
+        for (key, value) in form_urlencoded::parse(body).into_owned() {
+            match key.as_str() {
+                OAUTH_PARAM_KEY_TOKEN => token = Some(value),
+                OAUTH_PARAM_KEY_TOKEN_SECRET => token_secret = Some(Secret::from(value)),
+                _ => extra.push((key, value)),
+            }
+        }
+        Some(TokenResponse {
+            token: token?,
+            token_secret: token_secret?,
+            extra,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.extra
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        // https://tools.ietf.org/html/rfc5849#section-2.1
+        let response = TokenResponse::from_slice(
+            b"oauth_token=hh5s93j4hdidpola&oauth_token_secret=hdhd0244k9j7ao03\
+              &oauth_callback_confirmed=true",
+        )
+        .unwrap();
+        assert_eq!(response.token, "hh5s93j4hdidpola");
+        assert_eq!(response.token_secret.expose(), "hdhd0244k9j7ao03");
+        assert_eq!(response.get("oauth_callback_confirmed"), Some("true"));
+        assert!(TokenResponse::from_slice(b"oauth_token=hh5s93j4hdidpola").is_none());
+    }
+}
@@ -1,26 +1,41 @@
-use std::borrow::Cow;
+use alloc::borrow::Cow;
 
 pub const OAUTH_VALUE_VERSION: &str = "1.0";
 
 pub const OAUTH_VALUE_SIGMETHOD_HMACSHA1: &str = "HMAC-SHA1";
 pub const OAUTH_VALUE_SIGMETHOD_PLAINTEXT: &str = "PLAINTEXT";
-#[derive(Clone, Copy, Debug)]
+pub const OAUTH_VALUE_SIGMETHOD_RSASHA1: &str = "RSA-SHA1";
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SignatureMethod {
     PlainText,
     HmacSha1,
-    // TODO: add implementation
-    // RsaSha1,
+    /// Signed with the consumer's RSA private key, given in place of the
+    /// consumer secret as a PKCS#8 or PKCS#1 PEM document.
+    RsaSha1,
 }
 
-impl Into<&'static str> for SignatureMethod {
-    fn into(self) -> &'static str {
-        match self {
+impl From<SignatureMethod> for &'static str {
+    fn from(method: SignatureMethod) -> Self {
+        match method {
             SignatureMethod::PlainText => OAUTH_VALUE_SIGMETHOD_PLAINTEXT,
             SignatureMethod::HmacSha1 => OAUTH_VALUE_SIGMETHOD_HMACSHA1,
+            SignatureMethod::RsaSha1 => OAUTH_VALUE_SIGMETHOD_RSASHA1,
         }
     }
 }
 
+/// Characters a signer percent-encodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EncodingProfile {
+    /// Everything but the unreserved characters of RFC 3986, as RFC 5849 requires.
+    #[default]
+    Rfc3986,
+    /// RFC 3986, but `:` is left as is, so that base strings read `https:%2F%2F…`,
+    /// for services which compare against such a base string.
+    KeepColon,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum OAuthVersion<'a> {
     None,
     Default,
@@ -52,9 +67,9 @@ impl<'a> From<Cow<'a, str>> for OAuthVersion<'a> {
     }
 }
 
-impl<'a> Into<Option<Cow<'a, str>>> for OAuthVersion<'a> {
-    fn into(self) -> Option<Cow<'a, str>> {
-        match self {
+impl<'a> From<OAuthVersion<'a>> for Option<Cow<'a, str>> {
+    fn from(version: OAuthVersion<'a>) -> Self {
+        match version {
             OAuthVersion::None => None,
             OAuthVersion::Default => Some(Cow::from(OAUTH_VALUE_VERSION)),
             OAuthVersion::Custom(s) => Some(s),
@@ -0,0 +1,86 @@
+use crate::builder::OAuthSigner;
+use crate::parameters::OAuthParameter;
+use crate::v1::{Secrets, SignedContent, Signer};
+use std::borrow::Cow;
+use std::io;
+
+const OAUTH_PARAM_KEY_X_AUTH_MODE: &str = "x_auth_mode";
+const OAUTH_PARAM_KEY_X_AUTH_PASSWORD: &str = "x_auth_password";
+const OAUTH_PARAM_KEY_X_AUTH_USERNAME: &str = "x_auth_username";
+pub const OAUTH_VALUE_X_AUTH_MODE_CLIENT_AUTH: &str = "client_auth";
+
+impl<'a> Signer<'a, ()> {
+    /// Sign an xAuth request exchanging a user's name and password for
+    /// token credentials. The signer is built for the access token URL.
+    ///
+    /// Send the `x_auth_` parameters in the body (`SignedContent::form_body`)
+    /// and parse the response with `v1::TokenResponse`.
+    pub fn sign_xauth<TUsername, TPassword>(
+        self,
+        username: TUsername,
+        password: TPassword,
+        secrets: &Secrets<'a, ()>,
+    ) -> io::Result<SignedContent<'a>>
+    where
+        TUsername: Into<Cow<'a, str>>,
+        TPassword: Into<Cow<'a, str>>,
+    {
+        self.sign(
+            vec![
+                (
+                    OAUTH_PARAM_KEY_X_AUTH_USERNAME.into(),
+                    OAuthParameter::StringValue(username.into()),
+                ),
+                (
+                    OAUTH_PARAM_KEY_X_AUTH_PASSWORD.into(),
+                    OAuthParameter::StringValue(password.into()),
+                ),
+                (
+                    OAUTH_PARAM_KEY_X_AUTH_MODE.into(),
+                    OAUTH_VALUE_X_AUTH_MODE_CLIENT_AUTH.into(),
+                ),
+            ],
+            secrets,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sign_xauth() {
+        // https://developer.twitter.com/en/docs/authentication/oauth-1-0a/xauth
+        let mut signer = Signer::<()>::new(
+            "JvyS7DO2qd6NNTsXJ4E7zA",
+            "https://api.twitter.com/oauth/access_token",
+            "POST",
+        );
+        signer
+            .nonce("6AN2dKRzxyGhmIXUKSmp1JcB4pckM8rD3frKMTmVAo")
+            .timestamp(1_284_565_601);
+        let signed = signer
+            .sign_xauth(
+                "oauth_test_exec",
+                "twitter-xauth",
+                &Secrets::<()>::new("9z6157pUbOBqtbm0A0q4r29Y2EYzIHlUwbF4Cl9c"),
+            )
+            .unwrap();
+        assert_eq!(
+            signed.base_string,
+            "POST&https%3A%2F%2Fapi.twitter.com%2Foauth%2Faccess_token\
+             &oauth_consumer_key%3DJvyS7DO2qd6NNTsXJ4E7zA\
+             %26oauth_nonce%3D6AN2dKRzxyGhmIXUKSmp1JcB4pckM8rD3frKMTmVAo\
+             %26oauth_signature_method%3DHMAC-SHA1%26oauth_timestamp%3D1284565601\
+             %26oauth_version%3D1.0%26x_auth_mode%3Dclient_auth\
+             %26x_auth_password%3Dtwitter-xauth%26x_auth_username%3Doauth_test_exec"
+        );
+        assert_eq!(signed.signature, "1L1oXQmawZAkQ47FHLwcOV+kjwc=");
+        assert_eq!(
+            signed.form_body(),
+            "x_auth_username=oauth_test_exec&x_auth_password=twitter-xauth&x_auth_mode=client_auth"
+        );
+        assert!(!signed.authorization_header().contains("x_auth"));
+    }
+}
@@ -1,80 +1,61 @@
-use chrono::Utc;
-use hmac::{Hmac, Mac};
-use percent_encoding::{utf8_percent_encode, AsciiSet};
-use sha1::Sha1;
-use std::borrow::Cow;
-
-use std::collections::HashMap;
-use uuid::Uuid;
+//! The `OAuthSignBuilder` of earlier releases, kept for code written against it.
+//!
+//! Signing is delegated to `v1::Signer`; new code should use it directly.
+#![allow(deprecated)]
 
+use crate::builder::OAuthSigner;
+use crate::parameters::OAuthParameter;
 use crate::util;
-
-type HmacSha1 = Hmac<Sha1>;
-
-// https://tools.ietf.org/html/rfc5849#section-3.6
-// * ALPHA, DIGIT, '-', '.', '_', '~' MUST NOT be encoded.
-// * All other characters MUST be encoded.
-// * The two hexadecimal characters used to represent encoded
-//   characters MUST be uppercase.
-const TARGETS_FOR_PARAMS: &AsciiSet = &percent_encoding::NON_ALPHANUMERIC
-    .remove(b'-')
-    .remove(b'.')
-    .remove(b'_')
-    .remove(b'~');
-
-const DEFAULT_SIGNATURE: &str = "HMAC-SHA1";
-const OAUTH_VERSION: &str = "1.0";
-
-const OAUTH_HEADER: &str = "OAuth";
-const OAUTH_PARAM_KEY_NONCE: &str = "oauth_nonce";
-const OAUTH_PARAM_KEY_CALLBACK: &str = "oauth_callback";
-const OAUTH_PARAM_KEY_SIGNATURE_METHOD: &str = "oauth_signature_method";
-const OAUTH_PARAM_KEY_TIMESTAMP: &str = "oauth_timestamp";
-const OAUTH_PARAM_KEY_VERSION: &str = "oauth_version";
-const OAUTH_PARAM_KEY_CONSUMER_KEY: &str = "oauth_consumer_key";
+use crate::v1::{
+    OAuthVersion, Secrets, SignatureMethod, Signer, OAUTH_VALUE_SIGMETHOD_HMACSHA1,
+    OAUTH_VALUE_SIGMETHOD_PLAINTEXT, OAUTH_VALUE_VERSION,
+};
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::io;
 
 /// OAuth Signature Builder
+///
+/// Signing fails with an `InvalidInput` error if the signature method is
+/// neither `HMAC-SHA1` nor `PLAINTEXT`.
+#[deprecated(note = "use `v1::Signer`")]
 pub struct OAuthSignBuilder<TokenType> {
     oauth_consumer_key: String,
-    oauth_nonce: String,
+    oauth_nonce: Option<String>,
     oauth_signature_method: String,
     oauth_version: Option<String>,
     oauth_timestamp: Option<i64>,
     oauth_token: TokenType,
-    encoded_parameters: HashMap<String, String>,
+    parameters: Vec<(String, String)>,
 }
 
 /// OAuthSignBuilder that not bound with an Access Token.
 impl OAuthSignBuilder<()> {
     /// Constructor of OAuthSignBuilder with OAuth consumer_key.
-    pub fn new<K: Into<String> + ?Sized>(consumer_key: K) -> Self {
-        // set with default values.
-        OAuthSignBuilder {
-            oauth_consumer_key: consumer_key.into(),
-            oauth_nonce: format!("{}", Uuid::new_v4()),
-            oauth_signature_method: DEFAULT_SIGNATURE.into(),
-            oauth_version: Some(OAUTH_VERSION.into()),
-            oauth_timestamp: None,
-            oauth_token: (),
-            encoded_parameters: std::collections::HashMap::new(),
-        }
+    pub fn new<K: Into<String>>(consumer_key: K) -> Self {
+        OAuthSignBuilder::with_token(consumer_key.into(), ())
     }
 
     /// Generate OAuth signature to specified URL.
-    pub fn sign_to_url(&self, url: &url::Url, http_method: &str, consumer_secret: &str) -> String {
-        let (endpoint, url_encoded_query) = util::url_to_endpoint_and_queries(url);
-        self.sign_impl(
-            endpoint,
-            url_encoded_query,
-            http_method,
-            consumer_secret,
-            None,
-        )
+    pub fn sign_to_url(
+        &self,
+        url: &url::Url,
+        http_method: &str,
+        consumer_secret: &str,
+    ) -> io::Result<String> {
+        let (endpoint, query) = util::url_to_endpoint_and_queries(url)?;
+        let signer = Signer::<()>::new(self.oauth_consumer_key.as_str(), endpoint, http_method);
+        self.sign_impl(signer, query, &Secrets::<()>::new(consumer_secret))
     }
 
     /// Generate OAuth signature with specified endpoint and query.
-    pub fn sign(&self, endpoint: &str, http_method: &str, consumer_secret: &str) -> String {
-        self.sign_impl(endpoint, HashMap::new(), http_method, consumer_secret, None)
+    pub fn sign(
+        &self,
+        endpoint: &str,
+        http_method: &str,
+        consumer_secret: &str,
+    ) -> io::Result<String> {
+        self.sign_with_query(endpoint, "", http_method, consumer_secret)
     }
 
     pub fn sign_with_query(
@@ -83,13 +64,12 @@ impl OAuthSignBuilder<()> {
         url_encoded_query: &str,
         http_method: &str,
         consumer_secret: &str,
-    ) -> String {
+    ) -> io::Result<String> {
+        let signer = Signer::<()>::new(self.oauth_consumer_key.as_str(), endpoint, http_method);
         self.sign_impl(
-            endpoint,
-            util::query_to_hashmap(url_encoded_query),
-            http_method,
-            consumer_secret,
-            None,
+            signer,
+            util::destructure_query(url_encoded_query)?,
+            &Secrets::<()>::new(consumer_secret),
         )
     }
 }
@@ -97,16 +77,7 @@ impl OAuthSignBuilder<()> {
 // token-installed impl
 impl OAuthSignBuilder<String> {
     pub fn new_with_token(consumer_key: impl Into<String>, oauth_token: impl Into<String>) -> Self {
-        // set with default values.
-        OAuthSignBuilder {
-            oauth_consumer_key: consumer_key.into(),
-            oauth_nonce: format!("{}", Uuid::new_v4()),
-            oauth_signature_method: DEFAULT_SIGNATURE.into(),
-            oauth_version: Some(OAUTH_VERSION.into()),
-            oauth_timestamp: None,
-            oauth_token: oauth_token.into(),
-            encoded_parameters: std::collections::HashMap::new(),
-        }
+        OAuthSignBuilder::with_token(consumer_key.into(), oauth_token.into())
     }
 
     pub fn sign_to_url(
@@ -115,14 +86,12 @@ impl OAuthSignBuilder<String> {
         http_method: &str,
         consumer_secret: &str,
         token_secret: &str,
-    ) -> String {
-        let (endpoint, url_encoded_query) = util::url_to_endpoint_and_queries(url);
+    ) -> io::Result<String> {
+        let (endpoint, query) = util::url_to_endpoint_and_queries(url)?;
         self.sign_impl(
-            endpoint,
-            url_encoded_query,
-            http_method,
-            consumer_secret,
-            Some((&self.oauth_token, token_secret)),
+            self.signer(endpoint, http_method),
+            query,
+            &Secrets::<crate::Secret>::new(consumer_secret, token_secret),
         )
     }
 
@@ -133,23 +102,43 @@ impl OAuthSignBuilder<String> {
         http_method: &str,
         consumer_secret: &str,
         token_secret: &str,
-    ) -> String {
+    ) -> io::Result<String> {
         self.sign_impl(
+            self.signer(endpoint, http_method),
+            util::destructure_query(url_encoded_query)?,
+            &Secrets::<crate::Secret>::new(consumer_secret, token_secret),
+        )
+    }
+
+    fn signer<'a>(&'a self, endpoint: &'a str, http_method: &'a str) -> Signer<'a, Cow<'a, str>> {
+        Signer::<Cow<str>>::new(
+            self.oauth_consumer_key.as_str(),
             endpoint,
-            util::query_to_hashmap(url_encoded_query),
             http_method,
-            consumer_secret,
-            Some((&self.oauth_token, token_secret)),
+            self.oauth_token.as_str(),
         )
     }
 }
 
 impl<TokenType> OAuthSignBuilder<TokenType> {
+    fn with_token(consumer_key: String, oauth_token: TokenType) -> Self {
+        OAuthSignBuilder {
+            oauth_consumer_key: consumer_key,
+            oauth_nonce: None,
+            oauth_signature_method: OAUTH_VALUE_SIGMETHOD_HMACSHA1.into(),
+            oauth_version: Some(OAUTH_VALUE_VERSION.into()),
+            oauth_timestamp: None,
+            oauth_token,
+            parameters: Vec::new(),
+        }
+    }
+
     pub fn oauth_nonce(&mut self, nonce: impl Into<String>) -> &mut OAuthSignBuilder<TokenType> {
-        self.oauth_nonce = nonce.into();
+        self.oauth_nonce = Some(nonce.into());
         self
     }
 
+    /// `HMAC-SHA1` or `PLAINTEXT`.
     pub fn oauth_signature_method(
         &mut self,
         signature_method: impl Into<String>,
@@ -179,177 +168,178 @@ impl<TokenType> OAuthSignBuilder<TokenType> {
             oauth_version: self.oauth_version,
             oauth_timestamp: self.oauth_timestamp,
             oauth_token: token.into(),
-            encoded_parameters: self.encoded_parameters,
+            parameters: self.parameters,
         }
     }
 
+    /// Add a parameter, replacing the value of `key` if it was added before.
     pub fn add_param(&mut self, key: &str, value: &str) -> &mut OAuthSignBuilder<TokenType> {
-        self.encoded_parameters.insert(
-            utf8_percent_encode(key, TARGETS_FOR_PARAMS).to_string(),
-            utf8_percent_encode(value, TARGETS_FOR_PARAMS).to_string(),
-        );
+        match self.parameters.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.parameters.push((key.to_string(), value.to_string())),
+        }
         self
     }
 
+    /// Add a percent-encoded parameter.
     pub fn add_param_encoded(
         &mut self,
         key: impl Into<String>,
         value: impl Into<String>,
     ) -> &mut OAuthSignBuilder<TokenType> {
-        self.encoded_parameters.insert(key.into(), value.into());
-        self
+        let decode = |s: String| percent_decode_str(&s).decode_utf8_lossy().into_owned();
+        let (key, value) = (decode(key.into()), decode(value.into()));
+        self.add_param(&key, &value)
     }
 
-    /// Encode the parameter (core method).
+    /// Sign the query and the added parameters with `signer`, configured as this builder.
     ///
-    /// # Parameters
-    /// - endpoint: access endpoint.
-    /// - url_encoded_query: query on the URL, must be encoded.
-    /// - http_method: HTTP method, ex)"GET", "POST", ...
-    /// - consumer_secret: consumer secret key.
-    /// - token_and_secret: access token and secret.
-    /// # Returns
-    /// authorization signature (not encoded).
-    /// # Note
-    /// all of parameters except of url_encoded_query should be URL encoded.
-    fn sign_impl(
-        &self,
-        endpoint: &str,
-        url_encoded_query: HashMap<&str, &str>,
-        http_method: &str,
-        consumer_secret: &str,
-        token_and_secret: Option<(&str, &str)>,
-    ) -> String {
-        // destructuring token and secret
-        let (token, token_secret) = token_and_secret
-            .map(|(t, s)| (Some(t), Some(s)))
-            .unwrap_or((None, None));
-
-        // build authorization basic parameters
-        let timestamp = format!(
-            "{}",
-            self.oauth_timestamp
-                .unwrap_or_else(|| Utc::now().timestamp())
-        );
-        let mut basic_params = vec![
-            ("oauth_consumer_key", &self.oauth_consumer_key),
-            ("oauth_signature_method", &self.oauth_signature_method),
-            ("oauth_timestamp", &timestamp),
-            ("oauth_nonce", &self.oauth_nonce),
-        ];
-        if let Some(oauth_version) = &self.oauth_version {
-            basic_params.push(("oauth_version", oauth_version));
+    /// Returns the signature, not percent-encoded.
+    fn sign_impl<'a, T, TSecrets>(
+        &'a self,
+        mut signer: Signer<'a, T>,
+        query: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+        secrets: &TSecrets,
+    ) -> io::Result<String>
+    where
+        Signer<'a, T>: OAuthSigner<'a, TSecrets, io::Result<crate::v1::SignedContent<'a>>>,
+    {
+        let signature_method = match self.oauth_signature_method.as_str() {
+            OAUTH_VALUE_SIGMETHOD_HMACSHA1 => SignatureMethod::HmacSha1,
+            OAUTH_VALUE_SIGMETHOD_PLAINTEXT => SignatureMethod::PlainText,
+            method => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unsupported signature method: {}", method),
+                ))
+            }
+        };
+        let version = match &self.oauth_version {
+            Some(version) if version == OAUTH_VALUE_VERSION => OAuthVersion::Default,
+            Some(version) => OAuthVersion::Custom(version.as_str().into()),
+            None => OAuthVersion::None,
+        };
+        signer.signature_method(signature_method).version(version);
+        if let Some(nonce) = &self.oauth_nonce {
+            signer.nonce(nonce.as_str());
         }
-        let stringify_token = token.map(|t| t.to_string());
-        if let Some(oauth_token) = &stringify_token {
-            basic_params.push(("oauth_token", &oauth_token));
+        if let Some(timestamp) = self.oauth_timestamp {
+            signer.timestamp(timestamp);
         }
-        let basic_params = basic_params
-            .iter()
-            .map(|(k, v)| {
-                (
-                    utf8_percent_encode(&k, TARGETS_FOR_PARAMS),
-                    utf8_percent_encode(&v, TARGETS_FOR_PARAMS),
-                )
-            })
-            .map(|(k, v)| (Cow::from(k), Cow::from(v)))
-            .collect::<Vec<(Cow<str>, Cow<str>)>>();
-        let query_params = url_encoded_query
-            .iter()
-            .map(|(&k, &v)| (Cow::from(k), Cow::from(v)))
-            .collect::<Vec<(Cow<str>, Cow<str>)>>();
-        let post_params = self
-            .encoded_parameters
-            .iter()
-            .map(|(k, v)| (Cow::from(k), Cow::from(v)))
-            .collect::<Vec<(Cow<str>, Cow<str>)>>();
-
-        // join above three parameters
-        let mut params = [basic_params, query_params, post_params].concat::<(Cow<str>, Cow<str>)>();
-
-        // then, alphabetic sort by key
-        params.sort();
-
-        // create signature string to sign
-        let param_str = params
-            .iter()
-            .filter(|(k, _)| k != "realm") // "realm" is a special parameter
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect::<Vec<String>>()
-            .join("&");
-        // println!("param: {:#?}", param_str);
-
-        // create signature to sign
-        let http_method = http_method.to_ascii_uppercase();
-        let encoded_http_method = utf8_percent_encode(&http_method, TARGETS_FOR_PARAMS);
-        let encoded_endpoint = utf8_percent_encode(endpoint, TARGETS_FOR_PARAMS);
-        let encoded_params = utf8_percent_encode(&param_str, TARGETS_FOR_PARAMS);
-        let base_str = format!(
-            "{}&{}&{}",
-            encoded_http_method, encoded_endpoint, encoded_params
-        );
-        // println!("base: {:#?}", base_str);
-
-        // create sign key
-        let token_secret = token_secret.unwrap_or("");
-        let encoded_cs = utf8_percent_encode(consumer_secret, TARGETS_FOR_PARAMS);
-        let encoded_ts = utf8_percent_encode(token_secret, TARGETS_FOR_PARAMS);
-        let sign_key = format!("{}&{}", encoded_cs, encoded_ts);
-        // println!("sign_key: {:#?}", sign_key);
-
-        // generate sign
-        let mut mac = HmacSha1::new_varkey(sign_key.as_bytes())
-            .expect("this message is dummy; SHA-1 accepts any size of keys.");
-        mac.input(base_str.as_bytes());
-        let hash = mac.result().code();
-        base64::encode(&hash)
+        let params = query
+            .into_iter()
+            .chain(
+                self.parameters
+                    .iter()
+                    .map(|(k, v)| (Cow::from(k.as_str()), Cow::from(v.as_str()))),
+            )
+            .map(|(k, v)| (k, OAuthParameter::StringValue(v)))
+            .collect();
+        Ok(signer.sign(params, secrets)?.signature)
     }
 }
 
-#[test]
-fn test_builder() {
-    let builder = OAuthSignBuilder::new("ck")
-        .add_param("param1", "value1_ud_plus+hy-qu'dq\"1-9!@#$%^&*()_+-NS=[]");
-}
-
-#[test]
-fn test_sign() {
-    // https://developer.twitter.com/ja/docs/basics/authentication/guides/creating-a-signature
-    let endpoint = url::Url::parse("https://api.twitter.com/1.1/statuses/update.json").unwrap();
-    let method = "post";
-    let c_key = "xvz1evFS4wEEPTGEFPHBog";
-    let c_secret = "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw";
-    let nonce = "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg";
-    let timestamp = 1_318_622_958;
-    let token = "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb";
-    let token_secret = "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE";
-    let sign = OAuthSignBuilder::new_with_token(c_key, token)
-        .oauth_nonce(nonce)
-        .oauth_timestamp(timestamp)
-        .add_param("include_entities", "true")
-        .add_param(
-            "status",
-            "Hello Ladies + Gentlemen, a signed OAuth request!",
-        )
-        .sign_to_url(&endpoint, method, c_secret, token_secret);
-    println!("{:#?}", sign);
-    assert_eq!(sign, "hCtSmYh+iHYCEqBWrE7C7hYmtUk=");
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let mut builder = OAuthSignBuilder::new("ck");
+        builder
+            .add_param("param1", "value1_ud_plus+hy-qu'dq\"1-9!@#$%^&*()_+-NS=[]")
+            .add_param_encoded("param2", "a%20b")
+            .add_param("param2", "c");
+        assert_eq!(
+            builder.parameters,
+            vec![
+                (
+                    "param1".to_string(),
+                    "value1_ud_plus+hy-qu'dq\"1-9!@#$%^&*()_+-NS=[]".to_string()
+                ),
+                ("param2".to_string(), "c".to_string()),
+            ]
+        );
+    }
 
-    // https://tools.ietf.org/html/rfc5849
-    let endpoint = url::Url::parse("https://photos.example.net/initiate").unwrap();
-    let method = "post";
-    let c_key = "dpf43f3p2l4k3l03";
-    let c_secret = "kd94hf93k423kf44";
-    let nonce = "wIjqoS";
-    let timestamp = 137_131_200;
+    #[test]
+    fn test_sign() {
+        // https://developer.twitter.com/ja/docs/basics/authentication/guides/creating-a-signature
+        let endpoint = url::Url::parse("https://api.twitter.com/1.1/statuses/update.json").unwrap();
+        let method = "post";
+        let c_key = "xvz1evFS4wEEPTGEFPHBog";
+        let c_secret = "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw";
+        let nonce = "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg";
+        let timestamp = 1_318_622_958;
+        let token = "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb";
+        let token_secret = "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE";
+        let sign = OAuthSignBuilder::new_with_token(c_key, token)
+            .oauth_nonce(nonce)
+            .oauth_timestamp(timestamp)
+            .add_param("include_entities", "true")
+            .add_param(
+                "status",
+                "Hello Ladies + Gentlemen, a signed OAuth request!",
+            )
+            .sign_to_url(&endpoint, method, c_secret, token_secret)
+            .unwrap();
+        assert_eq!(sign, "hCtSmYh+iHYCEqBWrE7C7hYmtUk=");
+
+        // the same request, with `include_entities` in the query.
+        let sign = OAuthSignBuilder::new(c_key)
+            .oauth_token(token)
+            .oauth_nonce(nonce)
+            .oauth_timestamp(timestamp)
+            .add_param(
+                "status",
+                "Hello Ladies + Gentlemen, a signed OAuth request!",
+            )
+            .sign(
+                "https://api.twitter.com/1.1/statuses/update.json",
+                "include_entities=true",
+                method,
+                c_secret,
+                token_secret,
+            )
+            .unwrap();
+        assert_eq!(sign, "hCtSmYh+iHYCEqBWrE7C7hYmtUk=");
+
+        // https://tools.ietf.org/html/rfc5849
+        let endpoint = url::Url::parse("https://photos.example.net/initiate").unwrap();
+        let method = "post";
+        let c_key = "dpf43f3p2l4k3l03";
+        let c_secret = "kd94hf93k423kf44";
+        let nonce = "wIjqoS";
+        let timestamp = 137_131_200;
+
+        let sign = OAuthSignBuilder::new(c_key)
+            .oauth_nonce(nonce)
+            .oauth_version(None as Option<String>)
+            .oauth_timestamp(timestamp)
+            .add_param("realm", "photos")
+            .add_param("oauth_callback", "http://printer.example.com/ready")
+            .sign_to_url(&endpoint, method, c_secret)
+            .unwrap();
+        assert_eq!(sign, "74KNZJeDHnMBp0EMJ9ZHt/XKycU=");
+    }
 
-    let sign = OAuthSignBuilder::new(c_key)
-        .oauth_nonce(nonce)
-        .oauth_version(None as Option<String>)
-        .oauth_timestamp(timestamp)
-        .add_param("realm", "photos")
-        .add_param("oauth_callback", "http://printer.example.com/ready")
-        .sign_to_url(&endpoint, method, c_secret);
-    println!("{:#?}", sign);
-    assert_eq!(sign, "74KNZJeDHnMBp0EMJ9ZHt/XKycU=");
+    #[test]
+    fn test_signature_method() {
+        let mut builder = OAuthSignBuilder::new("dpf43f3p2l4k3l03");
+        builder.oauth_nonce("wIjqoS").oauth_timestamp(137_131_200);
+        let sign = |builder: &OAuthSignBuilder<()>| {
+            builder.sign(
+                "https://photos.example.net/initiate",
+                "POST",
+                "kd94hf93k423kf44",
+            )
+        };
+        builder.oauth_signature_method("PLAINTEXT");
+        assert_eq!(sign(&builder).unwrap(), "kd94hf93k423kf44&");
+        for method in &["RSA-SHA1", "HMAC-SHA256", "hmac-sha1"] {
+            builder.oauth_signature_method(*method);
+            let e = sign(&builder).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::InvalidInput, "{}", method);
+        }
+    }
 }
@@ -0,0 +1,38 @@
+mod authorization;
+mod bearer;
+mod client;
+mod client_auth;
+mod dpop;
+mod id_token;
+mod introspection;
+mod jwks;
+mod jws;
+mod metadata;
+mod pkce;
+mod request;
+mod revocation;
+mod token;
+mod values;
+
+pub use authorization::AuthorizationRequest;
+pub use bearer::{BearerMethod, BearerSigner};
+pub use client::OAuthV2Client;
+pub use client_auth::ClientAuthentication;
+pub use dpop::{nonce_challenge, DPoPKey, DPoPProof, DPoPSigner, Jwk};
+pub use id_token::{
+    AddressClaim, IdToken, IdTokenError, IdTokenValidator, JoseHeader, StandardClaims,
+};
+pub use introspection::{IntrospectionRequest, IntrospectionResponse};
+pub use jwks::{JsonWebKey, JsonWebKeySet};
+pub use jws::JwsKey;
+pub use metadata::{AuthorizationServerMetadata, MetadataKind};
+pub use pkce::PkceVerifier;
+pub use request::EndpointRequest;
+pub use revocation::RevocationRequest;
+pub use token::{TokenRequest, TokenResponse};
+pub use values::{ErrorResponse, TokenTypeHint};
+
+pub(crate) const AUTHORIZATION_HEADER: &str = "Authorization";
+pub(crate) const DPOP_HEADER: &str = "DPoP";
+#[cfg(feature = "reqwest")]
+pub(crate) const DPOP_NONCE_HEADER: &str = "DPoP-Nonce";
@@ -0,0 +1,103 @@
+use crate::v2::pkce::{PkceVerifier, OAUTH_VALUE_CODE_CHALLENGE_METHOD_S256};
+use std::borrow::Cow;
+
+const OAUTH_PARAM_KEY_RESPONSE_TYPE: &str = "response_type";
+const OAUTH_PARAM_KEY_CLIENT_ID: &str = "client_id";
+const OAUTH_PARAM_KEY_REDIRECT_URI: &str = "redirect_uri";
+const OAUTH_PARAM_KEY_SCOPE: &str = "scope";
+const OAUTH_PARAM_KEY_STATE: &str = "state";
+const OAUTH_PARAM_KEY_CODE_CHALLENGE: &str = "code_challenge";
+const OAUTH_PARAM_KEY_CODE_CHALLENGE_METHOD: &str = "code_challenge_method";
+const OAUTH_VALUE_RESPONSE_TYPE_CODE: &str = "code";
+
+/// Authorization request of the authorization code grant.
+///
+/// https://tools.ietf.org/html/rfc6749#section-4.1.1
+pub struct AuthorizationRequest<'a> {
+    endpoint: Cow<'a, str>,
+    params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+impl<'a> AuthorizationRequest<'a> {
+    pub fn new<TEndpoint, TClientId>(endpoint: TEndpoint, client_id: TClientId) -> Self
+    where
+        TEndpoint: Into<Cow<'a, str>>,
+        TClientId: Into<Cow<'a, str>>,
+    {
+        AuthorizationRequest {
+            endpoint: endpoint.into(),
+            params: vec![
+                (
+                    OAUTH_PARAM_KEY_RESPONSE_TYPE.into(),
+                    OAUTH_VALUE_RESPONSE_TYPE_CODE.into(),
+                ),
+                (OAUTH_PARAM_KEY_CLIENT_ID.into(), client_id.into()),
+            ],
+        }
+    }
+
+    pub fn redirect_uri<T: Into<Cow<'a, str>>>(&mut self, redirect_uri: T) -> &mut Self {
+        self.param(OAUTH_PARAM_KEY_REDIRECT_URI, redirect_uri)
+    }
+
+    pub fn scope<T: Into<Cow<'a, str>>>(&mut self, scope: T) -> &mut Self {
+        self.param(OAUTH_PARAM_KEY_SCOPE, scope)
+    }
+
+    pub fn state<T: Into<Cow<'a, str>>>(&mut self, state: T) -> &mut Self {
+        self.param(OAUTH_PARAM_KEY_STATE, state)
+    }
+
+    /// Bind the request to `verifier` with the `S256` challenge method.
+    pub fn pkce(&mut self, verifier: &PkceVerifier) -> &mut Self {
+        self.param(OAUTH_PARAM_KEY_CODE_CHALLENGE, verifier.challenge())
+            .param(
+                OAUTH_PARAM_KEY_CODE_CHALLENGE_METHOD,
+                OAUTH_VALUE_CODE_CHALLENGE_METHOD_S256,
+            )
+    }
+
+    /// Extension parameter, e.g. `nonce` of OpenID Connect.
+    pub fn param<TKey, TValue>(&mut self, key: TKey, value: TValue) -> &mut Self
+    where
+        TKey: Into<Cow<'a, str>>,
+        TValue: Into<Cow<'a, str>>,
+    {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    /// URL to open in the user agent.
+    ///
+    /// Query parameters already present in the endpoint are retained.
+    pub fn url(&self) -> Result<String, url::ParseError> {
+        let mut url = url::Url::parse(&self.endpoint)?;
+        url.query_pairs_mut().extend_pairs(self.params.iter());
+        Ok(url.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_url() {
+        let verifier = PkceVerifier::new("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+        let mut request = AuthorizationRequest::new(
+            "https://server.example.com/authorize?tenant=a",
+            "s6BhdRkqt3",
+        );
+        request
+            .redirect_uri("https://client.example.com/cb")
+            .scope("openid profile")
+            .state("xyz")
+            .pkce(&verifier);
+        assert_eq!(
+            request.url().unwrap(),
+            "https://server.example.com/authorize?tenant=a&response_type=code&client_id=s6BhdRkqt3\
+             &redirect_uri=https%3A%2F%2Fclient.example.com%2Fcb&scope=openid+profile&state=xyz\
+             &code_challenge=E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM&code_challenge_method=S256"
+        );
+    }
+}
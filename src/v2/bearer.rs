@@ -0,0 +1,94 @@
+use crate::builder::OAuthSigner;
+use crate::parameters::{OAuthParameter, OAuthSignedContent, OAuthSignedParameter};
+use crate::secret::Secret;
+use crate::v2::AUTHORIZATION_HEADER;
+use std::borrow::Cow;
+use std::io;
+
+const OAUTH_PARAM_KEY_ACCESS_TOKEN: &str = "access_token";
+const BEARER_SCHEME: &str = "Bearer";
+
+/// Where the access token of a resource request is sent (RFC 6750 section 2).
+///
+/// The URI query parameter of section 2.3 is not supported, since it leaks
+/// the token into logs and histories.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BearerMethod {
+    /// `Authorization: Bearer` request header.
+    #[default]
+    Header,
+    /// `access_token` parameter of a form-encoded body; requests which carry
+    /// their parameters in the query, e.g. `GET`, can not be sent this way.
+    FormBody,
+}
+
+/// Authorizes a resource request with a Bearer access token (RFC 6750).
+#[derive(Clone, Debug, Default)]
+pub struct BearerSigner {
+    method: BearerMethod,
+}
+
+impl BearerSigner {
+    pub fn new() -> Self {
+        BearerSigner::default()
+    }
+
+    pub fn method(&mut self, method: BearerMethod) -> &mut Self {
+        self.method = method;
+        self
+    }
+}
+
+impl<'a> OAuthSigner<'a, Secret<'a>, io::Result<OAuthSignedContent>> for BearerSigner {
+    fn sign(
+        self,
+        param: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
+        access_token: &Secret<'a>,
+    ) -> io::Result<OAuthSignedContent> {
+        let token = access_token.expose().to_string();
+        let mut signed = OAuthSignedContent {
+            protocol_parameters: vec![(OAUTH_PARAM_KEY_ACCESS_TOKEN.to_string(), token.clone())],
+            ..OAuthSignedContent::default()
+        };
+        match self.method {
+            BearerMethod::Header => signed
+                .headers
+                .push((AUTHORIZATION_HEADER, format!("{} {}", BEARER_SCHEME, token))),
+            BearerMethod::FormBody => signed.parameters.push((
+                OAUTH_PARAM_KEY_ACCESS_TOKEN.to_string(),
+                OAuthSignedParameter::StringValue(token),
+            )),
+        }
+        signed.push_parameters(param);
+        Ok(signed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sign_bearer() {
+        let token = Secret::from("mF_9.B5f-4.1JqM");
+        let signed = BearerSigner::new()
+            .sign(vec![("q".into(), "a b".into())], &token)
+            .unwrap();
+        assert_eq!(
+            signed.headers,
+            vec![("Authorization", "Bearer mF_9.B5f-4.1JqM".to_string())]
+        );
+        assert_eq!(signed.form_body(), "q=a+b");
+
+        let mut signer = BearerSigner::new();
+        signer.method(BearerMethod::FormBody);
+        let signed = signer.sign(vec![], &token).unwrap();
+        assert!(signed.headers.is_empty());
+        assert_eq!(signed.form_body(), "access_token=mF_9.B5f-4.1JqM");
+        let mut url = url::Url::parse("https://server.example.com/resource").unwrap();
+        assert!(signed.request_body("GET", &mut url).is_err());
+        assert_eq!(url.query(), None);
+        assert!(signed.request_body("POST", &mut url).unwrap().is_some());
+        assert!(!format!("{:?}", signed).contains("mF_9"));
+    }
+}
@@ -0,0 +1,240 @@
+use crate::v2::authorization::AuthorizationRequest;
+use crate::v2::client_auth::ClientAuthentication;
+use crate::v2::id_token::IdTokenValidator;
+use crate::v2::introspection::IntrospectionRequest;
+use crate::v2::jwks::JsonWebKeySet;
+use crate::v2::metadata::AuthorizationServerMetadata;
+use crate::v2::pkce::PkceVerifier;
+use crate::v2::request::EndpointRequest;
+use crate::v2::revocation::RevocationRequest;
+use crate::v2::token::TokenRequest;
+use crate::v2::values::TokenTypeHint;
+use std::borrow::Cow;
+
+/// OAuth 2 client bound to the endpoints of one authorization server.
+pub struct OAuthV2Client<'a> {
+    auth: ClientAuthentication<'a>,
+    issuer: Option<Cow<'a, str>>,
+    authorization_endpoint: Option<Cow<'a, str>>,
+    token_endpoint: Option<Cow<'a, str>>,
+    device_authorization_endpoint: Option<Cow<'a, str>>,
+    revocation_endpoint: Option<Cow<'a, str>>,
+    introspection_endpoint: Option<Cow<'a, str>>,
+    jwks_uri: Option<Cow<'a, str>>,
+}
+
+impl<'a> OAuthV2Client<'a> {
+    pub fn new(auth: ClientAuthentication<'a>) -> Self {
+        OAuthV2Client {
+            auth,
+            issuer: None,
+            authorization_endpoint: None,
+            token_endpoint: None,
+            device_authorization_endpoint: None,
+            revocation_endpoint: None,
+            introspection_endpoint: None,
+            jwks_uri: None,
+        }
+    }
+
+    /// Configure every endpoint published in the discovery document.
+    pub fn from_metadata(
+        metadata: &'a AuthorizationServerMetadata,
+        auth: ClientAuthentication<'a>,
+    ) -> Self {
+        let endpoint = |e: &'a Option<String>| e.as_deref().map(Cow::from);
+        OAuthV2Client {
+            auth,
+            issuer: Some(Cow::from(metadata.issuer.as_str())),
+            authorization_endpoint: endpoint(&metadata.authorization_endpoint),
+            token_endpoint: endpoint(&metadata.token_endpoint),
+            device_authorization_endpoint: endpoint(&metadata.device_authorization_endpoint),
+            revocation_endpoint: endpoint(&metadata.revocation_endpoint),
+            introspection_endpoint: endpoint(&metadata.introspection_endpoint),
+            jwks_uri: endpoint(&metadata.jwks_uri),
+        }
+    }
+
+    pub fn issuer<T: Into<Cow<'a, str>>>(&mut self, issuer: T) -> &mut Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    pub fn authorization_endpoint<T: Into<Cow<'a, str>>>(&mut self, endpoint: T) -> &mut Self {
+        self.authorization_endpoint = Some(endpoint.into());
+        self
+    }
+
+    pub fn token_endpoint<T: Into<Cow<'a, str>>>(&mut self, endpoint: T) -> &mut Self {
+        self.token_endpoint = Some(endpoint.into());
+        self
+    }
+
+    pub fn device_authorization_endpoint<T: Into<Cow<'a, str>>>(
+        &mut self,
+        endpoint: T,
+    ) -> &mut Self {
+        self.device_authorization_endpoint = Some(endpoint.into());
+        self
+    }
+
+    pub fn revocation_endpoint<T: Into<Cow<'a, str>>>(&mut self, endpoint: T) -> &mut Self {
+        self.revocation_endpoint = Some(endpoint.into());
+        self
+    }
+
+    pub fn introspection_endpoint<T: Into<Cow<'a, str>>>(&mut self, endpoint: T) -> &mut Self {
+        self.introspection_endpoint = Some(endpoint.into());
+        self
+    }
+
+    pub fn jwks_uri<T: Into<Cow<'a, str>>>(&mut self, uri: T) -> &mut Self {
+        self.jwks_uri = Some(uri.into());
+        self
+    }
+
+    pub fn auth(&self) -> &ClientAuthentication<'a> {
+        &self.auth
+    }
+
+    pub fn client_id(&self) -> &str {
+        self.auth.client_id()
+    }
+
+    pub fn get_issuer(&self) -> Option<&str> {
+        self.issuer.as_deref()
+    }
+
+    pub fn get_authorization_endpoint(&self) -> Option<&str> {
+        self.authorization_endpoint.as_deref()
+    }
+
+    pub fn get_token_endpoint(&self) -> Option<&str> {
+        self.token_endpoint.as_deref()
+    }
+
+    pub fn get_device_authorization_endpoint(&self) -> Option<&str> {
+        self.device_authorization_endpoint.as_deref()
+    }
+
+    pub fn get_revocation_endpoint(&self) -> Option<&str> {
+        self.revocation_endpoint.as_deref()
+    }
+
+    pub fn get_introspection_endpoint(&self) -> Option<&str> {
+        self.introspection_endpoint.as_deref()
+    }
+
+    pub fn get_jwks_uri(&self) -> Option<&str> {
+        self.jwks_uri.as_deref()
+    }
+
+    /// Validator for ID tokens issued to this client, if the issuer is known.
+    pub fn id_token_validator<'v>(
+        &'v self,
+        jwks: &'v JsonWebKeySet,
+    ) -> Option<IdTokenValidator<'v>> {
+        let issuer = self.issuer.as_deref()?;
+        Some(IdTokenValidator::new(issuer, self.client_id(), jwks))
+    }
+
+    /// Authorization code request, if the authorization endpoint is known.
+    pub fn authorization_request(&self) -> Option<AuthorizationRequest<'a>> {
+        Some(AuthorizationRequest::new(
+            self.authorization_endpoint.clone()?,
+            self.client_id().to_string(),
+        ))
+    }
+
+    /// Token request exchanging `code`, if the token endpoint is known.
+    pub fn exchange_code<TCode, TRedirectUri>(
+        &self,
+        code: TCode,
+        redirect_uri: TRedirectUri,
+        verifier: Option<&PkceVerifier>,
+    ) -> Option<EndpointRequest<'a>>
+    where
+        TCode: Into<Cow<'a, str>>,
+        TRedirectUri: Into<Cow<'a, str>>,
+    {
+        let mut request =
+            TokenRequest::authorization_code(self.token_endpoint.clone()?, code, redirect_uri);
+        if let Some(verifier) = verifier {
+            request.code_verifier(verifier);
+        }
+        Some(request.build(&self.auth))
+    }
+
+    /// Token request exchanging `refresh_token`, if the token endpoint is known.
+    pub fn refresh<T: Into<Cow<'a, str>>>(&self, refresh_token: T) -> Option<EndpointRequest<'a>> {
+        let request = TokenRequest::refresh_token(self.token_endpoint.clone()?, refresh_token);
+        Some(request.build(&self.auth))
+    }
+
+    /// Introspection request for `token`, if the introspection endpoint is known.
+    pub fn introspect<T: Into<Cow<'a, str>>>(
+        &self,
+        token: T,
+        hint: Option<TokenTypeHint>,
+    ) -> Option<EndpointRequest<'a>> {
+        let mut request = IntrospectionRequest::new(self.introspection_endpoint.clone()?, token);
+        if let Some(hint) = hint {
+            request.token_type_hint(hint);
+        }
+        Some(request.build(&self.auth))
+    }
+
+    /// Revocation request for `token`, if the revocation endpoint is known.
+    pub fn revoke<T: Into<Cow<'a, str>>>(
+        &self,
+        token: T,
+        hint: Option<TokenTypeHint>,
+    ) -> Option<EndpointRequest<'a>> {
+        let mut request = RevocationRequest::new(self.revocation_endpoint.clone()?, token);
+        if let Some(hint) = hint {
+            request.token_type_hint(hint);
+        }
+        Some(request.build(&self.auth))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_metadata() {
+        let metadata = AuthorizationServerMetadata::from_slice(
+            br#"{
+                "issuer": "https://server.example.com",
+                "token_endpoint": "https://server.example.com/token",
+                "revocation_endpoint": "https://server.example.com/revoke",
+                "jwks_uri": "https://server.example.com/jwks.json"
+            }"#,
+        )
+        .unwrap();
+        let client = OAuthV2Client::from_metadata(
+            &metadata,
+            ClientAuthentication::client_secret_post("s6BhdRkqt3", "secret"),
+        );
+        assert_eq!(client.get_issuer(), Some("https://server.example.com"));
+        assert_eq!(
+            client.get_token_endpoint(),
+            Some("https://server.example.com/token")
+        );
+        assert_eq!(
+            client.get_jwks_uri(),
+            Some("https://server.example.com/jwks.json")
+        );
+        assert!(client.introspect("token", None).is_none());
+
+        let request = client
+            .revoke("45ghiukldjahdnhzdauz", Some(TokenTypeHint::RefreshToken))
+            .unwrap();
+        assert_eq!(request.endpoint, "https://server.example.com/revoke");
+        assert_eq!(
+            request.body(),
+            "token=45ghiukldjahdnhzdauz&token_type_hint=refresh_token&client_id=s6BhdRkqt3&client_secret=secret"
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use crate::secret::Secret;
+use crate::v2::jws::{self, JwsKey};
+use crate::v2::request::EndpointRequest;
+use crate::v2::values::OAUTH_VALUE_CLIENT_ASSERTION_TYPE_JWT_BEARER;
+use crate::v2::AUTHORIZATION_HEADER;
+use chrono::Utc;
+use serde::Serialize;
+use std::borrow::Cow;
+use url::form_urlencoded;
+use uuid::Uuid;
+
+const OAUTH_PARAM_KEY_CLIENT_ID: &str = "client_id";
+const OAUTH_PARAM_KEY_CLIENT_SECRET: &str = "client_secret";
+const OAUTH_PARAM_KEY_CLIENT_ASSERTION: &str = "client_assertion";
+const OAUTH_PARAM_KEY_CLIENT_ASSERTION_TYPE: &str = "client_assertion_type";
+
+pub const OAUTH_VALUE_AUTH_METHOD_NONE: &str = "none";
+pub const OAUTH_VALUE_AUTH_METHOD_CLIENT_SECRET_BASIC: &str = "client_secret_basic";
+pub const OAUTH_VALUE_AUTH_METHOD_CLIENT_SECRET_POST: &str = "client_secret_post";
+pub const OAUTH_VALUE_AUTH_METHOD_PRIVATE_KEY_JWT: &str = "private_key_jwt";
+
+const CLIENT_ASSERTION_JWT_TYPE: &str = "JWT";
+// lifetime of client assertions, in seconds.
+const CLIENT_ASSERTION_LIFETIME: i64 = 300;
+
+/// How a client authenticates itself to the authorization server.
+///
+/// https://tools.ietf.org/html/rfc6749#section-2.3
+/// https://openid.net/specs/openid-connect-core-1_0.html#ClientAuthentication
+#[derive(Debug)]
+pub enum ClientAuthentication<'a> {
+    /// Public clients only identify themselves with `client_id`.
+    None { client_id: Cow<'a, str> },
+    ClientSecretBasic {
+        client_id: Cow<'a, str>,
+        client_secret: Secret<'a>,
+    },
+    ClientSecretPost {
+        client_id: Cow<'a, str>,
+        client_secret: Secret<'a>,
+    },
+    /// Client assertion signed with the client's private key (RFC 7523).
+    /// `audience` defaults to the endpoint the request is sent to.
+    PrivateKeyJwt {
+        client_id: Cow<'a, str>,
+        key: &'a JwsKey,
+        key_id: Option<Cow<'a, str>>,
+        audience: Option<Cow<'a, str>>,
+    },
+}
+
+#[derive(Serialize)]
+struct ClientAssertionHeader<'a> {
+    alg: &'a str,
+    typ: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kid: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct ClientAssertionClaims<'a> {
+    iss: &'a str,
+    sub: &'a str,
+    aud: &'a str,
+    jti: String,
+    iat: i64,
+    exp: i64,
+}
+
+impl<'a> ClientAuthentication<'a> {
+    pub fn none<T: Into<Cow<'a, str>>>(client_id: T) -> Self {
+        ClientAuthentication::None {
+            client_id: client_id.into(),
+        }
+    }
+
+    pub fn client_secret_basic<TId, TSecret>(client_id: TId, client_secret: TSecret) -> Self
+    where
+        TId: Into<Cow<'a, str>>,
+        TSecret: Into<Cow<'a, str>>,
+    {
+        ClientAuthentication::ClientSecretBasic {
+            client_id: client_id.into(),
+            client_secret: Secret::new(client_secret),
+        }
+    }
+
+    pub fn client_secret_post<TId, TSecret>(client_id: TId, client_secret: TSecret) -> Self
+    where
+        TId: Into<Cow<'a, str>>,
+        TSecret: Into<Cow<'a, str>>,
+    {
+        ClientAuthentication::ClientSecretPost {
+            client_id: client_id.into(),
+            client_secret: Secret::new(client_secret),
+        }
+    }
+
+    pub fn private_key_jwt<T: Into<Cow<'a, str>>>(client_id: T, key: &'a JwsKey) -> Self {
+        ClientAuthentication::PrivateKeyJwt {
+            client_id: client_id.into(),
+            key,
+            key_id: None,
+            audience: None,
+        }
+    }
+
+    pub fn client_id(&self) -> &str {
+        match self {
+            ClientAuthentication::None { client_id }
+            | ClientAuthentication::ClientSecretBasic { client_id, .. }
+            | ClientAuthentication::ClientSecretPost { client_id, .. }
+            | ClientAuthentication::PrivateKeyJwt { client_id, .. } => client_id,
+        }
+    }
+
+    /// Name of this method in `token_endpoint_auth_method` metadata.
+    pub fn method_name(&self) -> &'static str {
+        match self {
+            ClientAuthentication::None { .. } => OAUTH_VALUE_AUTH_METHOD_NONE,
+            ClientAuthentication::ClientSecretBasic { .. } => {
+                OAUTH_VALUE_AUTH_METHOD_CLIENT_SECRET_BASIC
+            }
+            ClientAuthentication::ClientSecretPost { .. } => {
+                OAUTH_VALUE_AUTH_METHOD_CLIENT_SECRET_POST
+            }
+            ClientAuthentication::PrivateKeyJwt { .. } => OAUTH_VALUE_AUTH_METHOD_PRIVATE_KEY_JWT,
+        }
+    }
+
+    /// Add the client credentials to `request`.
+    pub fn apply(&self, request: &mut EndpointRequest<'a>) {
+        match self {
+            ClientAuthentication::None { client_id } => {
+                request.param(OAUTH_PARAM_KEY_CLIENT_ID, client_id.clone());
+            }
+            ClientAuthentication::ClientSecretBasic {
+                client_id,
+                client_secret,
+            } => {
+                // credentials are form-encoded before being joined.
+                // https://tools.ietf.org/html/rfc6749#section-2.3.1
+                let credentials = Secret::from(format!(
+                    "{}:{}",
+                    form_urlencoded::byte_serialize(client_id.as_bytes()).collect::<String>(),
+                    form_urlencoded::byte_serialize(client_secret.expose().as_bytes())
+                        .collect::<String>()
+                ));
+                request.header(
+                    AUTHORIZATION_HEADER,
+                    format!("Basic {}", base64::encode(credentials.expose())),
+                );
+            }
+            ClientAuthentication::ClientSecretPost {
+                client_id,
+                client_secret,
+            } => {
+                request
+                    .param(OAUTH_PARAM_KEY_CLIENT_ID, client_id.clone())
+                    .param(
+                        OAUTH_PARAM_KEY_CLIENT_SECRET,
+                        client_secret.expose().to_string(),
+                    );
+            }
+            ClientAuthentication::PrivateKeyJwt {
+                client_id,
+                key,
+                key_id,
+                audience,
+            } => {
+                let audience = audience.as_deref().unwrap_or(&request.endpoint);
+                let assertion = client_assertion(client_id, key, key_id.as_deref(), audience);
+                request
+                    .param(OAUTH_PARAM_KEY_CLIENT_ID, client_id.clone())
+                    .param(
+                        OAUTH_PARAM_KEY_CLIENT_ASSERTION_TYPE,
+                        OAUTH_VALUE_CLIENT_ASSERTION_TYPE_JWT_BEARER,
+                    )
+                    .param(OAUTH_PARAM_KEY_CLIENT_ASSERTION, assertion);
+            }
+        }
+    }
+}
+
+fn client_assertion(client_id: &str, key: &JwsKey, key_id: Option<&str>, audience: &str) -> String {
+    let issued_at = Utc::now().timestamp();
+    let header = ClientAssertionHeader {
+        alg: key.algorithm(),
+        typ: CLIENT_ASSERTION_JWT_TYPE,
+        kid: key_id,
+    };
+    let claims = ClientAssertionClaims {
+        iss: client_id,
+        sub: client_id,
+        aud: audience,
+        jti: format!("{}", Uuid::new_v4()),
+        iat: issued_at,
+        exp: issued_at + CLIENT_ASSERTION_LIFETIME,
+    };
+    jws::encode_jws(&header, &claims, |input| key.sign(input))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use p256::ecdsa::{signature::Verifier, Signature, SigningKey};
+    use rand_core::OsRng;
+
+    fn form_value<'r>(request: &'r EndpointRequest, key: &str) -> Option<&'r str> {
+        request
+            .form
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_ref())
+    }
+
+    #[test]
+    fn test_client_secret_basic() {
+        let mut request = EndpointRequest::new("https://server.example.com/token");
+        // https://tools.ietf.org/html/rfc6749#section-2.3.1
+        ClientAuthentication::client_secret_basic("s6BhdRkqt3", "gX1fBat3bV").apply(&mut request);
+        assert!(request.form.is_empty());
+        assert_eq!(
+            request.headers,
+            vec![(
+                "Authorization",
+                "Basic czZCaGRSa3F0MzpnWDFmQmF0M2JW".to_string()
+            )]
+        );
+
+        // reserved characters are form-encoded first
+        let mut request = EndpointRequest::new("https://server.example.com/token");
+        ClientAuthentication::client_secret_basic("client:1", "p@ss word").apply(&mut request);
+        let expected = format!("Basic {}", base64::encode("client%3A1:p%40ss+word"));
+        assert_eq!(request.headers[0].1, expected);
+    }
+
+    #[test]
+    fn test_client_secret_post() {
+        let mut request = EndpointRequest::new("https://server.example.com/token");
+        ClientAuthentication::client_secret_post("s6BhdRkqt3", "7Fjfp0ZBr1KtDRbnfVdmIw")
+            .apply(&mut request);
+        assert!(request.headers.is_empty());
+        assert_eq!(
+            request.body(),
+            "client_id=s6BhdRkqt3&client_secret=7Fjfp0ZBr1KtDRbnfVdmIw"
+        );
+    }
+
+    #[test]
+    fn test_debug_redacted() {
+        let auth = ClientAuthentication::client_secret_post("s6BhdRkqt3", "7Fjfp0ZBr1KtDRbnfVdmIw");
+        let debug = format!("{:?}", auth);
+        assert!(debug.contains("s6BhdRkqt3"));
+        assert!(!debug.contains("7Fjfp0ZBr1KtDRbnfVdmIw"));
+
+        let key = JwsKey::Es256(SigningKey::random(&mut OsRng));
+        assert_eq!(format!("{:?}", key), "JwsKey(ES256, [REDACTED])");
+    }
+
+    #[test]
+    fn test_private_key_jwt() {
+        let signing_key = SigningKey::random(&mut OsRng);
+        let verifying_key = *signing_key.verifying_key();
+        let key = JwsKey::Es256(signing_key);
+        let mut request = EndpointRequest::new("https://server.example.com/token");
+        ClientAuthentication::PrivateKeyJwt {
+            client_id: "s6BhdRkqt3".into(),
+            key: &key,
+            key_id: Some("key-1".into()),
+            audience: None,
+        }
+        .apply(&mut request);
+        assert_eq!(form_value(&request, "client_id"), Some("s6BhdRkqt3"));
+        assert_eq!(
+            form_value(&request, "client_assertion_type"),
+            Some("urn:ietf:params:oauth:client-assertion-type:jwt-bearer")
+        );
+
+        let assertion = form_value(&request, "client_assertion").unwrap();
+        let segments = assertion.split('.').collect::<Vec<&str>>();
+        let decode = |s: &str| base64::decode_config(s, base64::URL_SAFE_NO_PAD).unwrap();
+        let header: serde_json::Value = serde_json::from_slice(&decode(segments[0])).unwrap();
+        let claims: serde_json::Value = serde_json::from_slice(&decode(segments[1])).unwrap();
+        assert_eq!(header["alg"], "ES256");
+        assert_eq!(header["kid"], "key-1");
+        assert_eq!(claims["iss"], "s6BhdRkqt3");
+        assert_eq!(claims["sub"], "s6BhdRkqt3");
+        assert_eq!(claims["aud"], "https://server.example.com/token");
+        assert_eq!(
+            claims["exp"].as_i64().unwrap() - claims["iat"].as_i64().unwrap(),
+            CLIENT_ASSERTION_LIFETIME
+        );
+        let signature = Signature::from_slice(&decode(segments[2])).unwrap();
+        let signing_input = format!("{}.{}", segments[0], segments[1]);
+        assert!(verifying_key
+            .verify(signing_input.as_bytes(), &signature)
+            .is_ok());
+    }
+}
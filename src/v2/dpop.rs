@@ -0,0 +1,370 @@
+use crate::builder::OAuthSigner;
+use crate::parameters::{OAuthParameter, OAuthSignedContent};
+use crate::secret::fmt_redacted_key;
+use crate::util;
+use crate::v2::jws::{self, base64url_encode, JWS_ALGORITHM_ES256};
+use chrono::Utc;
+use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+use rand_core::OsRng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::fmt;
+use uuid::Uuid;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::v2::{ErrorResponse, AUTHORIZATION_HEADER, DPOP_HEADER};
+
+// https://tools.ietf.org/html/rfc9449#section-4.2
+const DPOP_JWT_TYPE: &str = "dpop+jwt";
+const DPOP_ERROR_USE_NONCE: &str = "use_dpop_nonce";
+
+const JWK_KEY_TYPE: &str = "EC";
+const JWK_CURVE: &str = "P-256";
+
+/// EC P-256 key pair bound to the DPoP proofs and tokens of a client.
+pub struct DPoPKey {
+    signing_key: SigningKey,
+}
+
+impl fmt::Debug for DPoPKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_redacted_key(f, "DPoPKey", JWS_ALGORITHM_ES256)
+    }
+}
+
+/// Public part of a `DPoPKey`, formatted as a JSON Web Key.
+///
+/// Members are declared in lexicographic order, so the serialized form is
+/// also the input of the RFC 7638 thumbprint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Jwk {
+    pub crv: String,
+    pub kty: String,
+    pub x: String,
+    pub y: String,
+}
+
+impl DPoPKey {
+    /// Generate a new key pair with the OS random number generator.
+    pub fn generate() -> Self {
+        DPoPKey {
+            signing_key: SigningKey::random(&mut OsRng),
+        }
+    }
+
+    /// Restore a key pair from its 32-byte secret scalar.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        SigningKey::from_slice(bytes)
+            .ok()
+            .map(|signing_key| DPoPKey { signing_key })
+    }
+
+    /// Secret scalar of this key pair, to persist it between sessions.
+    ///
+    /// The returned buffer is zeroed when dropped.
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = self.signing_key.to_bytes();
+        let secret = Zeroizing::new(bytes.to_vec());
+        bytes[..].zeroize();
+        secret
+    }
+
+    pub fn jwk(&self) -> Jwk {
+        let point = self.signing_key.verifying_key().to_encoded_point(false);
+        // uncompressed points always carry both coordinates.
+        Jwk {
+            crv: JWK_CURVE.to_string(),
+            kty: JWK_KEY_TYPE.to_string(),
+            x: point.x().map(base64url_encode).unwrap_or_default(),
+            y: point.y().map(base64url_encode).unwrap_or_default(),
+        }
+    }
+
+    /// JWK thumbprint (RFC 7638), used as `dpop_jkt` in authorization requests.
+    pub fn thumbprint(&self) -> String {
+        let jwk = serde_json::to_vec(&self.jwk()).unwrap_or_default();
+        base64url_encode(&Sha256::digest(&jwk))
+    }
+
+    fn sign_jws(&self, header: &impl Serialize, claims: &impl Serialize) -> String {
+        jws::encode_jws(header, claims, |input| {
+            let signature: Signature = self.signing_key.sign(input);
+            signature.to_bytes().to_vec()
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct ProofHeader<'a> {
+    typ: &'a str,
+    alg: &'a str,
+    jwk: Jwk,
+}
+
+#[derive(Serialize)]
+struct ProofClaims<'a> {
+    jti: &'a str,
+    htm: &'a str,
+    htu: &'a str,
+    iat: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ath: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nonce: Option<&'a str>,
+}
+
+/// DPoP proof created for a single HTTP request.
+pub struct DPoPProof<'a> {
+    /// Value of the `DPoP` request header.
+    pub proof: String,
+    pub jti: Cow<'a, str>,
+    pub issued_at: i64,
+    pub access_token: Option<Cow<'a, str>>,
+}
+
+impl<'a> DPoPProof<'a> {
+    /// Value of the `Authorization` request header, when the proof accompanies an access token.
+    pub fn authorization(&self) -> Option<String> {
+        self.access_token
+            .as_ref()
+            .map(|token| format!("{} {}", DPOP_HEADER, token))
+    }
+}
+
+/// The proof and the access token as headers; the parameters of the request
+/// are not covered by the proof, so they are added with `push_parameters`.
+impl From<DPoPProof<'_>> for OAuthSignedContent {
+    fn from(proof: DPoPProof<'_>) -> Self {
+        let mut headers = vec![(DPOP_HEADER, proof.proof.clone())];
+        if let Some(authorization) = proof.authorization() {
+            headers.push((AUTHORIZATION_HEADER, authorization));
+        }
+        OAuthSignedContent {
+            headers,
+            ..OAuthSignedContent::default()
+        }
+    }
+}
+
+/// Builds a DPoP proof (RFC 9449) for a request to `endpoint`.
+pub struct DPoPSigner<'a> {
+    endpoint: Cow<'a, str>,
+    http_method: Cow<'a, str>,
+    access_token: Option<Cow<'a, str>>,
+    nonce: Option<Cow<'a, str>>,
+    jti: Option<Cow<'a, str>>,
+    issued_at: Option<i64>,
+}
+
+impl<'a> DPoPSigner<'a> {
+    pub fn new<TEndpoint, THttpMethod>(endpoint: TEndpoint, http_method: THttpMethod) -> Self
+    where
+        TEndpoint: Into<Cow<'a, str>>,
+        THttpMethod: Into<Cow<'a, str>>,
+    {
+        DPoPSigner {
+            endpoint: endpoint.into(),
+            http_method: http_method.into(),
+            access_token: None,
+            nonce: None,
+            jti: None,
+            issued_at: None,
+        }
+    }
+
+    /// Bind the proof to an access token; this adds the `ath` claim.
+    pub fn access_token<T: Into<Cow<'a, str>>>(&mut self, token: T) -> &mut Self {
+        self.access_token = Some(token.into());
+        self
+    }
+
+    /// Nonce supplied by the server in its `DPoP-Nonce` header.
+    pub fn nonce<T: Into<Cow<'a, str>>>(&mut self, nonce: T) -> &mut Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    pub fn jti<T: Into<Cow<'a, str>>>(&mut self, jti: T) -> &mut Self {
+        self.jti = Some(jti.into());
+        self
+    }
+
+    pub fn issued_at(&mut self, timestamp: i64) -> &mut Self {
+        self.issued_at = Some(timestamp);
+        self
+    }
+}
+
+/// Parameters are not covered by DPoP proofs, so `param` is ignored.
+impl<'a> OAuthSigner<'a, DPoPKey, Result<DPoPProof<'a>, url::ParseError>> for DPoPSigner<'a> {
+    fn sign(
+        self,
+        _param: Vec<(Cow<'a, str>, OAuthParameter<'a>)>,
+        key: &DPoPKey,
+    ) -> Result<DPoPProof<'a>, url::ParseError> {
+        // htu is the target URI without query and fragment parts.
+        let mut url = url::Url::parse(&self.endpoint)?;
+        url.set_fragment(None);
+        let htu = util::url_to_endpoint(&url);
+
+        let jti = self
+            .jti
+            .unwrap_or_else(|| Cow::from(format!("{}", Uuid::new_v4())));
+        let issued_at = self.issued_at.unwrap_or_else(|| Utc::now().timestamp());
+        let http_method = self.http_method.to_ascii_uppercase();
+        let header = ProofHeader {
+            typ: DPOP_JWT_TYPE,
+            alg: JWS_ALGORITHM_ES256,
+            jwk: key.jwk(),
+        };
+        let claims = ProofClaims {
+            jti: &jti,
+            htm: &http_method,
+            htu,
+            iat: issued_at,
+            ath: self.access_token.as_deref().map(access_token_hash),
+            nonce: self.nonce.as_deref(),
+        };
+        let proof = key.sign_jws(&header, &claims);
+        Ok(DPoPProof {
+            proof,
+            jti,
+            issued_at,
+            access_token: self.access_token,
+        })
+    }
+}
+
+/// Check a response for a `use_dpop_nonce` error and return the nonce to retry with.
+///
+/// Resource servers report the error in `WWW-Authenticate` with status 401,
+/// authorization servers in the JSON `body` of a 400. A `DPoP-Nonce` header
+/// alone is no signal: servers may attach it to any response (RFC 9449 section 8.2).
+pub fn nonce_challenge<'h>(
+    status: u16,
+    www_authenticate: Option<&str>,
+    body: &[u8],
+    dpop_nonce: Option<&'h str>,
+) -> Option<&'h str> {
+    let nonce = dpop_nonce.filter(|n| !n.is_empty())?;
+    match status {
+        400 if ErrorResponse::from_slice(body).is_ok_and(|e| e.error == DPOP_ERROR_USE_NONCE) => {
+            Some(nonce)
+        }
+        401 if www_authenticate.is_some_and(|v| v.contains(DPOP_ERROR_USE_NONCE)) => Some(nonce),
+        _ => None,
+    }
+}
+
+fn access_token_hash(token: &str) -> String {
+    base64url_encode(&Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use p256::ecdsa::{signature::Verifier, VerifyingKey};
+    use p256::EncodedPoint;
+
+    fn decode_segment(segment: &str) -> serde_json::Value {
+        let bytes = base64::decode_config(segment, base64::URL_SAFE_NO_PAD).unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_access_token_hash() {
+        // https://tools.ietf.org/html/rfc9449#section-7.1
+        assert_eq!(
+            access_token_hash("Kz~8mXK1EalYznwH-LC-1fBAo.4Ljp~zsPE_NeO.gxU"),
+            "fUHyO2r2Z3DZ53EsNrWBb0xWXoaNy59IiKCAqksmQEo"
+        );
+    }
+
+    #[test]
+    fn test_sign_proof() {
+        let key = DPoPKey::generate();
+        let mut signer = DPoPSigner::new("https://Server.example.com/token?q=1#frag", "post");
+        signer
+            .access_token("Kz~8mXK1EalYznwH-LC-1fBAo.4Ljp~zsPE_NeO.gxU")
+            .nonce("eyJ7S_zG.eyJH0-Z.HX4w-7v")
+            .jti("e1j3V_bKic8-LAEB")
+            .issued_at(1_562_262_618);
+        let proof = signer.sign(Vec::new(), &key).unwrap();
+        assert_eq!(
+            proof.authorization().unwrap(),
+            "DPoP Kz~8mXK1EalYznwH-LC-1fBAo.4Ljp~zsPE_NeO.gxU"
+        );
+
+        let segments = proof.proof.split('.').collect::<Vec<&str>>();
+        assert_eq!(segments.len(), 3);
+        let header = decode_segment(segments[0]);
+        let claims = decode_segment(segments[1]);
+        assert_eq!(header["typ"], "dpop+jwt");
+        assert_eq!(header["alg"], "ES256");
+        assert_eq!(header["jwk"]["x"], key.jwk().x.as_str());
+        assert_eq!(claims["htm"], "POST");
+        assert_eq!(claims["htu"], "https://server.example.com/token");
+        assert_eq!(claims["iat"], 1_562_262_618);
+        assert_eq!(claims["jti"], "e1j3V_bKic8-LAEB");
+        assert_eq!(claims["nonce"], "eyJ7S_zG.eyJH0-Z.HX4w-7v");
+        assert_eq!(claims["ath"], "fUHyO2r2Z3DZ53EsNrWBb0xWXoaNy59IiKCAqksmQEo");
+
+        // verify with the public key embedded in the header
+        let jwk = key.jwk();
+        let decode = |c: &str| base64::decode_config(c, base64::URL_SAFE_NO_PAD).unwrap();
+        let point = EncodedPoint::from_affine_coordinates(
+            decode(&jwk.x).as_slice().into(),
+            decode(&jwk.y).as_slice().into(),
+            false,
+        );
+        let verifying_key = VerifyingKey::from_encoded_point(&point).unwrap();
+        let signature = Signature::from_slice(&decode(segments[2])).unwrap();
+        let signing_input = format!("{}.{}", segments[0], segments[1]);
+        assert!(verifying_key
+            .verify(signing_input.as_bytes(), &signature)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_sign_proof_without_token() {
+        let key = DPoPKey::generate();
+        let proof = DPoPSigner::new("https://server.example.com/token", "POST")
+            .sign(Vec::new(), &key)
+            .unwrap();
+        let claims = decode_segment(proof.proof.split('.').nth(1).unwrap());
+        assert!(claims.get("ath").is_none());
+        assert!(claims.get("nonce").is_none());
+        assert!(proof.authorization().is_none());
+    }
+
+    #[test]
+    fn test_key_roundtrip() {
+        let key = DPoPKey::generate();
+        let restored = DPoPKey::from_bytes(&key.to_bytes()).unwrap();
+        assert_eq!(key.jwk(), restored.jwk());
+        assert_eq!(key.thumbprint(), restored.thumbprint());
+        assert_eq!(key.thumbprint().len(), 43);
+    }
+
+    #[test]
+    fn test_nonce_challenge() {
+        let challenge = r#"DPoP error="use_dpop_nonce", error_description="Resource server requires nonce in DPoP proof""#;
+        assert_eq!(
+            nonce_challenge(401, Some(challenge), b"", Some("n-1")),
+            Some("n-1")
+        );
+        let body = br#"{"error":"use_dpop_nonce","error_description":"Authorization server requires nonce in DPoP proof"}"#;
+        assert_eq!(nonce_challenge(400, None, body, Some("n-2")), Some("n-2"));
+        // a spent authorization code must not be replayed.
+        let body = br#"{"error":"invalid_grant"}"#;
+        assert_eq!(nonce_challenge(400, None, body, Some("n-2")), None);
+        assert_eq!(nonce_challenge(400, None, b"", Some("n-2")), None);
+        assert_eq!(
+            nonce_challenge(401, Some("DPoP error=\"invalid_token\""), b"", Some("n")),
+            None
+        );
+        assert_eq!(nonce_challenge(200, None, b"", Some("n-3")), None);
+        assert_eq!(nonce_challenge(401, Some(challenge), b"", None), None);
+    }
+}
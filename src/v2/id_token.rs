@@ -0,0 +1,460 @@
+use crate::v2::jwks::JsonWebKeySet;
+use crate::v2::jws::{
+    base64url_decode, JWS_ALGORITHM_EDDSA, JWS_ALGORITHM_ES256, JWS_ALGORITHM_RS256,
+};
+use crate::v2::values::deserialize_audience;
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+// allowed clock skew, in seconds.
+const DEFAULT_LEEWAY: i64 = 60;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum IdTokenError {
+    /// Not a compact JWS, or the header/claims are not valid JSON.
+    Malformed,
+    UnsupportedAlgorithm(String),
+    /// No key of the JWK Set could verify the signature.
+    InvalidSignature,
+    InvalidIssuer(String),
+    InvalidAudience,
+    InvalidAuthorizedParty,
+    Expired,
+    IssuedInFuture,
+    NonceMismatch,
+}
+
+impl fmt::Display for IdTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdTokenError::Malformed => write!(f, "malformed ID token"),
+            IdTokenError::UnsupportedAlgorithm(alg) => write!(f, "unsupported algorithm: {}", alg),
+            IdTokenError::InvalidSignature => write!(f, "invalid signature"),
+            IdTokenError::InvalidIssuer(iss) => write!(f, "unexpected issuer: {}", iss),
+            IdTokenError::InvalidAudience => write!(f, "client is not an audience"),
+            IdTokenError::InvalidAuthorizedParty => write!(f, "client is not the authorized party"),
+            IdTokenError::Expired => write!(f, "ID token is expired"),
+            IdTokenError::IssuedInFuture => write!(f, "ID token is issued in the future"),
+            IdTokenError::NonceMismatch => write!(f, "nonce does not match"),
+        }
+    }
+}
+
+impl std::error::Error for IdTokenError {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct JoseHeader {
+    pub alg: String,
+    #[serde(default)]
+    pub kid: Option<String>,
+    #[serde(default)]
+    pub typ: Option<String>,
+}
+
+/// `address` claim.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct AddressClaim {
+    #[serde(default)]
+    pub formatted: Option<String>,
+    #[serde(default)]
+    pub street_address: Option<String>,
+    #[serde(default)]
+    pub locality: Option<String>,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub postal_code: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+}
+
+/// ID token claims and standard claims of OpenID Connect Core 1.0.
+///
+/// https://openid.net/specs/openid-connect-core-1_0.html#IDToken
+/// https://openid.net/specs/openid-connect-core-1_0.html#StandardClaims
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct StandardClaims {
+    pub iss: String,
+    pub sub: String,
+    #[serde(deserialize_with = "deserialize_audience")]
+    pub aud: Vec<String>,
+    pub exp: i64,
+    pub iat: i64,
+    #[serde(default)]
+    pub auth_time: Option<i64>,
+    #[serde(default)]
+    pub nonce: Option<String>,
+    #[serde(default)]
+    pub acr: Option<String>,
+    #[serde(default)]
+    pub amr: Vec<String>,
+    #[serde(default)]
+    pub azp: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub given_name: Option<String>,
+    #[serde(default)]
+    pub family_name: Option<String>,
+    #[serde(default)]
+    pub middle_name: Option<String>,
+    #[serde(default)]
+    pub nickname: Option<String>,
+    #[serde(default)]
+    pub preferred_username: Option<String>,
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub picture: Option<String>,
+    #[serde(default)]
+    pub website: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: Option<bool>,
+    #[serde(default)]
+    pub gender: Option<String>,
+    #[serde(default)]
+    pub birthdate: Option<String>,
+    #[serde(default)]
+    pub zoneinfo: Option<String>,
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub phone_number: Option<String>,
+    #[serde(default)]
+    pub phone_number_verified: Option<bool>,
+    #[serde(default)]
+    pub address: Option<AddressClaim>,
+    #[serde(default)]
+    pub updated_at: Option<i64>,
+    /// Claims not modeled above.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Validated ID token.
+#[derive(Debug, PartialEq)]
+pub struct IdToken {
+    pub header: JoseHeader,
+    pub claims: StandardClaims,
+    payload: Vec<u8>,
+}
+
+impl IdToken {
+    /// Deserialize the whole claim set into a custom type.
+    pub fn custom_claims<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_slice(&self.payload)
+    }
+}
+
+/// Validates ID tokens issued to a client (OpenID Connect Core 1.0, section 3.1.3.7).
+pub struct IdTokenValidator<'a> {
+    issuer: Cow<'a, str>,
+    client_id: Cow<'a, str>,
+    jwks: &'a JsonWebKeySet,
+    nonce: Option<Cow<'a, str>>,
+    leeway: i64,
+    now: Option<i64>,
+}
+
+impl<'a> IdTokenValidator<'a> {
+    pub fn new<TIssuer, TClientId>(
+        issuer: TIssuer,
+        client_id: TClientId,
+        jwks: &'a JsonWebKeySet,
+    ) -> Self
+    where
+        TIssuer: Into<Cow<'a, str>>,
+        TClientId: Into<Cow<'a, str>>,
+    {
+        IdTokenValidator {
+            issuer: issuer.into(),
+            client_id: client_id.into(),
+            jwks,
+            nonce: None,
+            leeway: DEFAULT_LEEWAY,
+            now: None,
+        }
+    }
+
+    /// Nonce sent in the authentication request; the token must carry the same value.
+    pub fn nonce<T: Into<Cow<'a, str>>>(&mut self, nonce: T) -> &mut Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Allowed clock skew for `exp` and `iat`, in seconds.
+    pub fn leeway(&mut self, seconds: i64) -> &mut Self {
+        self.leeway = seconds;
+        self
+    }
+
+    /// Current time used for `exp` and `iat` checks, in seconds since the epoch.
+    pub fn now(&mut self, timestamp: i64) -> &mut Self {
+        self.now = Some(timestamp);
+        self
+    }
+
+    pub fn validate(&self, id_token: &str) -> Result<IdToken, IdTokenError> {
+        let mut segments = id_token.split('.');
+        let (header, payload, signature) = match (
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+        ) {
+            (Some(h), Some(p), Some(s), None) => (h, p, s),
+            _ => return Err(IdTokenError::Malformed),
+        };
+        let decode = |s: &str| base64url_decode(s).ok_or(IdTokenError::Malformed);
+        let header_json: JoseHeader =
+            serde_json::from_slice(&decode(header)?).map_err(|_| IdTokenError::Malformed)?;
+        let payload_json = decode(payload)?;
+        let signature = decode(signature)?;
+
+        // signature ------------------------------------------------------------
+        let alg = header_json.alg.as_str();
+        if ![
+            JWS_ALGORITHM_RS256,
+            JWS_ALGORITHM_ES256,
+            JWS_ALGORITHM_EDDSA,
+        ]
+        .contains(&alg)
+        {
+            return Err(IdTokenError::UnsupportedAlgorithm(header_json.alg));
+        }
+        let signing_input = &id_token[..header.len() + 1 + payload.len()];
+        let verified = self
+            .jwks
+            .candidates(alg, header_json.kid.as_deref())
+            .any(|key| key.verify(alg, signing_input.as_bytes(), &signature));
+        if !verified {
+            return Err(IdTokenError::InvalidSignature);
+        }
+
+        // claims ---------------------------------------------------------------
+        let claims: StandardClaims =
+            serde_json::from_slice(&payload_json).map_err(|_| IdTokenError::Malformed)?;
+        if claims.iss != self.issuer {
+            return Err(IdTokenError::InvalidIssuer(claims.iss));
+        }
+        if !claims.aud.iter().any(|aud| aud == &self.client_id) {
+            return Err(IdTokenError::InvalidAudience);
+        }
+        // azp is required when there are other audiences, and must be us when present.
+        match &claims.azp {
+            Some(azp) if azp != &self.client_id => {
+                return Err(IdTokenError::InvalidAuthorizedParty)
+            }
+            None if claims.aud.len() > 1 => return Err(IdTokenError::InvalidAuthorizedParty),
+            _ => {}
+        }
+        let now = self.now.unwrap_or_else(|| Utc::now().timestamp());
+        if now > claims.exp + self.leeway {
+            return Err(IdTokenError::Expired);
+        }
+        if claims.iat > now + self.leeway {
+            return Err(IdTokenError::IssuedInFuture);
+        }
+        if let Some(nonce) = &self.nonce {
+            if claims.nonce.as_deref() != Some(nonce.as_ref()) {
+                return Err(IdTokenError::NonceMismatch);
+            }
+        }
+        Ok(IdToken {
+            header: header_json,
+            claims,
+            payload: payload_json,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::v2::jws::{self, base64url_encode};
+    use ed25519_dalek::Signer;
+    use p256::ecdsa::SigningKey;
+    use rand_core::OsRng;
+    use serde_json::json;
+
+    const ISSUER: &str = "https://server.example.com";
+    const CLIENT_ID: &str = "s6BhdRkqt3";
+    const NOW: i64 = 1_311_281_970;
+
+    fn claims() -> serde_json::Value {
+        json!({
+            "iss": ISSUER,
+            "sub": "24400320",
+            "aud": CLIENT_ID,
+            "nonce": "n-0S6_WzA2Mj",
+            "exp": NOW + 600,
+            "iat": NOW,
+            "auth_time": NOW - 10,
+            "email": "janedoe@example.com",
+            "email_verified": true,
+            "tenant": "acme"
+        })
+    }
+
+    fn es256_token(claims: &serde_json::Value) -> (String, JsonWebKeySet) {
+        let key = SigningKey::random(&mut OsRng);
+        let point = key.verifying_key().to_encoded_point(false);
+        let jwks = JsonWebKeySet::from_slice(
+            json!({"keys": [{
+                "kty": "EC", "crv": "P-256", "kid": "ec-1", "use": "sig",
+                "x": base64url_encode(point.x().unwrap()),
+                "y": base64url_encode(point.y().unwrap()),
+            }]})
+            .to_string()
+            .as_bytes(),
+        )
+        .unwrap();
+        let header = json!({"alg": "ES256", "kid": "ec-1"});
+        let token = jws::encode_jws(&header, claims, |input| {
+            let signature: p256::ecdsa::Signature =
+                p256::ecdsa::signature::Signer::sign(&key, input);
+            signature.to_vec()
+        });
+        (token, jwks)
+    }
+
+    #[test]
+    fn test_validate_es256() {
+        let (token, jwks) = es256_token(&claims());
+        let mut validator = IdTokenValidator::new(ISSUER, CLIENT_ID, &jwks);
+        validator.nonce("n-0S6_WzA2Mj").now(NOW);
+        let id_token = validator.validate(&token).unwrap();
+        assert_eq!(id_token.header.kid.as_deref(), Some("ec-1"));
+        assert_eq!(id_token.claims.sub, "24400320");
+        assert_eq!(id_token.claims.aud, vec![CLIENT_ID]);
+        assert_eq!(id_token.claims.email_verified, Some(true));
+        assert_eq!(id_token.claims.extra["tenant"], "acme");
+
+        #[derive(Deserialize)]
+        struct TenantClaims {
+            sub: String,
+            tenant: String,
+        }
+        let custom: TenantClaims = id_token.custom_claims().unwrap();
+        assert_eq!(custom.sub, "24400320");
+        assert_eq!(custom.tenant, "acme");
+    }
+
+    #[test]
+    fn test_validate_eddsa() {
+        let key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let jwks = JsonWebKeySet::from_slice(
+            json!({"keys": [{
+                "kty": "OKP", "crv": "Ed25519",
+                "x": base64url_encode(key.verifying_key().as_bytes()),
+            }]})
+            .to_string()
+            .as_bytes(),
+        )
+        .unwrap();
+        let token = jws::encode_jws(&json!({"alg": "EdDSA"}), &claims(), |input| {
+            key.sign(input).to_vec()
+        });
+        let mut validator = IdTokenValidator::new(ISSUER, CLIENT_ID, &jwks);
+        validator.now(NOW);
+        assert!(validator.validate(&token).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rs256() {
+        let key = rsa::RsaPrivateKey::new(&mut OsRng, 1024).unwrap();
+        let public_key = rsa::RsaPublicKey::from(&key);
+        let jwks = JsonWebKeySet::from_slice(
+            json!({"keys": [{
+                "kty": "RSA", "alg": "RS256",
+                "n": base64url_encode(&rsa::traits::PublicKeyParts::n(&public_key).to_bytes_be()),
+                "e": base64url_encode(&rsa::traits::PublicKeyParts::e(&public_key).to_bytes_be()),
+            }]})
+            .to_string()
+            .as_bytes(),
+        )
+        .unwrap();
+        let key = jws::JwsKey::Rs256(Box::new(rsa::pkcs1v15::SigningKey::new(key)));
+        let token = jws::encode_jws(&json!({"alg": "RS256"}), &claims(), |input| key.sign(input));
+        let mut validator = IdTokenValidator::new(ISSUER, CLIENT_ID, &jwks);
+        validator.now(NOW);
+        assert!(validator.validate(&token).is_ok());
+    }
+
+    #[test]
+    fn test_reject_invalid_tokens() {
+        let (token, jwks) = es256_token(&claims());
+        let mut validator = IdTokenValidator::new(ISSUER, CLIENT_ID, &jwks);
+        validator.now(NOW);
+
+        // tampered payload
+        let mut segments = token.split('.').collect::<Vec<&str>>();
+        let mut tampered_claims = claims();
+        tampered_claims["sub"] = json!("attacker");
+        let tampered_payload = base64url_encode(tampered_claims.to_string().as_bytes());
+        segments[1] = &tampered_payload;
+        assert_eq!(
+            validator.validate(&segments.join(".")),
+            Err(IdTokenError::InvalidSignature)
+        );
+        assert_eq!(validator.validate("a.b"), Err(IdTokenError::Malformed));
+
+        // issuer, audience and nonce
+        assert_eq!(
+            IdTokenValidator::new("https://other.example.com", CLIENT_ID, &jwks)
+                .now(NOW)
+                .validate(&token),
+            Err(IdTokenError::InvalidIssuer(ISSUER.to_string()))
+        );
+        assert_eq!(
+            IdTokenValidator::new(ISSUER, "other-client", &jwks)
+                .now(NOW)
+                .validate(&token),
+            Err(IdTokenError::InvalidAudience)
+        );
+        assert_eq!(
+            IdTokenValidator::new(ISSUER, CLIENT_ID, &jwks)
+                .now(NOW)
+                .nonce("another-nonce")
+                .validate(&token),
+            Err(IdTokenError::NonceMismatch)
+        );
+
+        // expiry with leeway
+        let mut validator = IdTokenValidator::new(ISSUER, CLIENT_ID, &jwks);
+        assert!(validator.now(NOW + 650).validate(&token).is_ok());
+        assert_eq!(
+            validator.now(NOW + 661).validate(&token),
+            Err(IdTokenError::Expired)
+        );
+        assert_eq!(
+            validator.now(NOW - 61).validate(&token),
+            Err(IdTokenError::IssuedInFuture)
+        );
+        assert!(validator.leeway(0).now(NOW - 1).validate(&token).is_err());
+    }
+
+    #[test]
+    fn test_authorized_party() {
+        let mut multiple = claims();
+        multiple["aud"] = json!([CLIENT_ID, "https://api.example.com"]);
+        let (token, jwks) = es256_token(&multiple);
+        let mut validator = IdTokenValidator::new(ISSUER, CLIENT_ID, &jwks);
+        validator.now(NOW);
+        assert_eq!(
+            validator.validate(&token),
+            Err(IdTokenError::InvalidAuthorizedParty)
+        );
+
+        multiple["azp"] = json!(CLIENT_ID);
+        let (token, jwks) = es256_token(&multiple);
+        let mut validator = IdTokenValidator::new(ISSUER, CLIENT_ID, &jwks);
+        assert!(validator.now(NOW).validate(&token).is_ok());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::v2::client_auth::ClientAuthentication;
+use crate::v2::request::EndpointRequest;
+use crate::v2::values::{deserialize_audience, TokenTypeHint};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+const OAUTH_PARAM_KEY_TOKEN: &str = "token";
+const OAUTH_PARAM_KEY_TOKEN_TYPE_HINT: &str = "token_type_hint";
+
+/// Token introspection request (RFC 7662).
+pub struct IntrospectionRequest<'a> {
+    endpoint: Cow<'a, str>,
+    token: Cow<'a, str>,
+    token_type_hint: Option<TokenTypeHint>,
+}
+
+impl<'a> IntrospectionRequest<'a> {
+    pub fn new<TEndpoint, TToken>(endpoint: TEndpoint, token: TToken) -> Self
+    where
+        TEndpoint: Into<Cow<'a, str>>,
+        TToken: Into<Cow<'a, str>>,
+    {
+        IntrospectionRequest {
+            endpoint: endpoint.into(),
+            token: token.into(),
+            token_type_hint: None,
+        }
+    }
+
+    pub fn token_type_hint(&mut self, hint: TokenTypeHint) -> &mut Self {
+        self.token_type_hint = Some(hint);
+        self
+    }
+
+    pub fn build(self, auth: &ClientAuthentication<'a>) -> EndpointRequest<'a> {
+        let mut request = EndpointRequest::new(self.endpoint);
+        request.param(OAUTH_PARAM_KEY_TOKEN, self.token);
+        if let Some(hint) = self.token_type_hint {
+            request.param(OAUTH_PARAM_KEY_TOKEN_TYPE_HINT, <&str>::from(hint));
+        }
+        auth.apply(&mut request);
+        request
+    }
+}
+
+/// Introspection response; members other than `active` are optional.
+///
+/// https://tools.ietf.org/html/rfc7662#section-2.2
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct IntrospectionResponse {
+    pub active: bool,
+    #[serde(default)]
+    pub scope: Option<String>,
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub token_type: Option<String>,
+    #[serde(default)]
+    pub exp: Option<i64>,
+    #[serde(default)]
+    pub iat: Option<i64>,
+    #[serde(default)]
+    pub nbf: Option<i64>,
+    #[serde(default)]
+    pub sub: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_audience")]
+    pub aud: Vec<String>,
+    #[serde(default)]
+    pub iss: Option<String>,
+    #[serde(default)]
+    pub jti: Option<String>,
+    /// Extension members of the response.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl IntrospectionResponse {
+    pub fn from_slice(body: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(body)
+    }
+
+    pub fn scopes(&self) -> Vec<&str> {
+        self.scope
+            .as_deref()
+            .map(|s| s.split(' ').filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the token is active and not expired at `now` (seconds since the epoch).
+    pub fn is_active_at(&self, now: i64) -> bool {
+        self.active && self.exp.is_none_or(|exp| now < exp) && self.nbf.is_none_or(|nbf| nbf <= now)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_build_request() {
+        let mut request =
+            IntrospectionRequest::new("https://server.example.com/introspect", "mF_9.B5f-4.1JqM");
+        request.token_type_hint(TokenTypeHint::AccessToken);
+        let request = request.build(&ClientAuthentication::client_secret_post(
+            "s6BhdRkqt3",
+            "secret",
+        ));
+        assert_eq!(request.endpoint, "https://server.example.com/introspect");
+        assert_eq!(
+            request.body(),
+            "token=mF_9.B5f-4.1JqM&token_type_hint=access_token&client_id=s6BhdRkqt3&client_secret=secret"
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        // https://tools.ietf.org/html/rfc7662#section-2.2
+        let body = br#"{
+            "active": true,
+            "client_id": "l238j323ds-23ij4",
+            "username": "jdoe",
+            "scope": "read write dolphin",
+            "sub": "Z5O3upPC88QrAjx00dis",
+            "aud": "https://protected.example.net/resource",
+            "iss": "https://server.example.com/",
+            "exp": 1419356238,
+            "iat": 1419350238,
+            "extension_field": "twenty-seven"
+        }"#;
+        let response = IntrospectionResponse::from_slice(body).unwrap();
+        assert!(response.active);
+        assert_eq!(response.scopes(), vec!["read", "write", "dolphin"]);
+        assert_eq!(response.aud, vec!["https://protected.example.net/resource"]);
+        assert_eq!(response.username.as_deref(), Some("jdoe"));
+        assert_eq!(response.extra["extension_field"], "twenty-seven");
+        assert!(response.is_active_at(1_419_350_238));
+        assert!(!response.is_active_at(1_419_356_238));
+
+        let inactive = IntrospectionResponse::from_slice(br#"{"active":false}"#).unwrap();
+        assert!(!inactive.active);
+        assert!(inactive.aud.is_empty());
+        assert!(inactive.scopes().is_empty());
+    }
+}
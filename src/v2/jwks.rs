@@ -0,0 +1,130 @@
+use crate::v2::jws::{
+    base64url_decode, JWS_ALGORITHM_EDDSA, JWS_ALGORITHM_ES256, JWS_ALGORITHM_RS256,
+};
+use p256::ecdsa::signature::Verifier;
+use serde::Deserialize;
+use sha2::Sha256;
+use std::convert::TryFrom;
+
+const JWK_KEY_TYPE_EC: &str = "EC";
+const JWK_KEY_TYPE_RSA: &str = "RSA";
+const JWK_KEY_TYPE_OKP: &str = "OKP";
+const JWK_CURVE_P256: &str = "P-256";
+const JWK_CURVE_ED25519: &str = "Ed25519";
+const JWK_USE_SIGNATURE: &str = "sig";
+
+/// Public key of a JWK Set (RFC 7517).
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct JsonWebKey {
+    pub kty: String,
+    #[serde(default)]
+    pub kid: Option<String>,
+    #[serde(default)]
+    pub alg: Option<String>,
+    #[serde(default, rename = "use")]
+    pub key_use: Option<String>,
+    // RSA
+    #[serde(default)]
+    pub n: Option<String>,
+    #[serde(default)]
+    pub e: Option<String>,
+    // EC / OKP
+    #[serde(default)]
+    pub crv: Option<String>,
+    #[serde(default)]
+    pub x: Option<String>,
+    #[serde(default)]
+    pub y: Option<String>,
+}
+
+/// JWK Set published at the `jwks_uri` of an authorization server.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct JsonWebKeySet {
+    pub keys: Vec<JsonWebKey>,
+}
+
+impl JsonWebKey {
+    /// Whether this key can verify signatures made with `alg`.
+    pub fn supports(&self, alg: &str) -> bool {
+        let usable = self
+            .key_use
+            .as_deref()
+            .is_none_or(|u| u == JWK_USE_SIGNATURE)
+            && self.alg.as_deref().is_none_or(|a| a == alg);
+        let crv = self.crv.as_deref();
+        usable
+            && match alg {
+                JWS_ALGORITHM_RS256 => self.kty == JWK_KEY_TYPE_RSA,
+                JWS_ALGORITHM_ES256 => self.kty == JWK_KEY_TYPE_EC && crv == Some(JWK_CURVE_P256),
+                JWS_ALGORITHM_EDDSA => {
+                    self.kty == JWK_KEY_TYPE_OKP && crv == Some(JWK_CURVE_ED25519)
+                }
+                _ => false,
+            }
+    }
+
+    /// Verify a JWS `signature` over `input` made with `alg`.
+    ///
+    /// Returns false for malformed keys and unsupported algorithms as well.
+    pub fn verify(&self, alg: &str, input: &[u8], signature: &[u8]) -> bool {
+        if !self.supports(alg) {
+            return false;
+        }
+        match alg {
+            JWS_ALGORITHM_RS256 => self.verify_rs256(input, signature),
+            JWS_ALGORITHM_ES256 => self.verify_es256(input, signature),
+            JWS_ALGORITHM_EDDSA => self.verify_eddsa(input, signature),
+            _ => None,
+        }
+        .unwrap_or(false)
+    }
+
+    fn verify_rs256(&self, input: &[u8], signature: &[u8]) -> Option<bool> {
+        let n = rsa::BigUint::from_bytes_be(&base64url_decode(self.n.as_deref()?)?);
+        let e = rsa::BigUint::from_bytes_be(&base64url_decode(self.e.as_deref()?)?);
+        let key = rsa::pkcs1v15::VerifyingKey::<Sha256>::new(rsa::RsaPublicKey::new(n, e).ok()?);
+        let signature = rsa::pkcs1v15::Signature::try_from(signature).ok()?;
+        Some(key.verify(input, &signature).is_ok())
+    }
+
+    fn verify_es256(&self, input: &[u8], signature: &[u8]) -> Option<bool> {
+        let x = base64url_decode(self.x.as_deref()?)?;
+        let y = base64url_decode(self.y.as_deref()?)?;
+        if x.len() != 32 || y.len() != 32 {
+            return None;
+        }
+        let point = p256::EncodedPoint::from_affine_coordinates(
+            x.as_slice().into(),
+            y.as_slice().into(),
+            false,
+        );
+        let key = p256::ecdsa::VerifyingKey::from_encoded_point(&point).ok()?;
+        let signature = p256::ecdsa::Signature::from_slice(signature).ok()?;
+        Some(key.verify(input, &signature).is_ok())
+    }
+
+    fn verify_eddsa(&self, input: &[u8], signature: &[u8]) -> Option<bool> {
+        let x = base64url_decode(self.x.as_deref()?)?;
+        let key = ed25519_dalek::VerifyingKey::try_from(x.as_slice()).ok()?;
+        let signature = ed25519_dalek::Signature::from_slice(signature).ok()?;
+        Some(key.verify(input, &signature).is_ok())
+    }
+}
+
+impl JsonWebKeySet {
+    pub fn from_slice(body: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(body)
+    }
+
+    /// Keys that may have produced a signature with `alg` and the key ID `kid`.
+    pub fn candidates<'k>(
+        &'k self,
+        alg: &'k str,
+        kid: Option<&'k str>,
+    ) -> impl Iterator<Item = &'k JsonWebKey> {
+        self.keys
+            .iter()
+            .filter(move |k| k.supports(alg))
+            .filter(move |k| kid.is_none() || k.kid.as_deref() == kid)
+    }
+}
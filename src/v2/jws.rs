@@ -0,0 +1,73 @@
+use crate::secret::fmt_redacted_key;
+use p256::ecdsa::signature::{SignatureEncoding, Signer};
+use rsa::pkcs8::DecodePrivateKey;
+use serde::Serialize;
+use sha2::Sha256;
+use std::fmt;
+
+pub(crate) const JWS_ALGORITHM_ES256: &str = "ES256";
+pub(crate) const JWS_ALGORITHM_RS256: &str = "RS256";
+pub(crate) const JWS_ALGORITHM_EDDSA: &str = "EdDSA";
+
+/// Private key used to sign JSON Web Signatures.
+pub enum JwsKey {
+    Es256(p256::ecdsa::SigningKey),
+    Rs256(Box<rsa::pkcs1v15::SigningKey<Sha256>>),
+}
+
+impl JwsKey {
+    /// Load an EC P-256 or RSA private key from a PKCS#8 PEM document.
+    pub fn from_pkcs8_pem(pem: &str) -> Option<Self> {
+        if let Ok(key) = p256::ecdsa::SigningKey::from_pkcs8_pem(pem) {
+            return Some(JwsKey::Es256(key));
+        }
+        rsa::RsaPrivateKey::from_pkcs8_pem(pem)
+            .ok()
+            .map(|key| JwsKey::Rs256(Box::new(rsa::pkcs1v15::SigningKey::new(key))))
+    }
+
+    /// Value of the JWS `alg` header for this key.
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            JwsKey::Es256(_) => JWS_ALGORITHM_ES256,
+            JwsKey::Rs256(_) => JWS_ALGORITHM_RS256,
+        }
+    }
+
+    pub(crate) fn sign(&self, input: &[u8]) -> Vec<u8> {
+        match self {
+            JwsKey::Es256(key) => {
+                let signature: p256::ecdsa::Signature = key.sign(input);
+                signature.to_vec()
+            }
+            JwsKey::Rs256(key) => key.sign(input).to_vec(),
+        }
+    }
+}
+
+impl fmt::Debug for JwsKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_redacted_key(f, "JwsKey", self.algorithm())
+    }
+}
+
+/// Serialize `header` and `claims` into a compact JWS signed by `sign`.
+pub(crate) fn encode_jws<F>(header: &impl Serialize, claims: &impl Serialize, sign: F) -> String
+where
+    F: FnOnce(&[u8]) -> Vec<u8>,
+{
+    // NOTE: serializing plain structs into JSON never fails.
+    let header = base64url_encode(&serde_json::to_vec(header).unwrap_or_default());
+    let claims = base64url_encode(&serde_json::to_vec(claims).unwrap_or_default());
+    let signing_input = format!("{}.{}", header, claims);
+    let signature = sign(signing_input.as_bytes());
+    format!("{}.{}", signing_input, base64url_encode(&signature))
+}
+
+pub(crate) fn base64url_encode<T: ?Sized + AsRef<[u8]>>(input: &T) -> String {
+    base64::encode_config(input, base64::URL_SAFE_NO_PAD)
+}
+
+pub(crate) fn base64url_decode(input: &str) -> Option<Vec<u8>> {
+    base64::decode_config(input, base64::URL_SAFE_NO_PAD).ok()
+}
@@ -0,0 +1,205 @@
+use crate::v2::client_auth::OAUTH_VALUE_AUTH_METHOD_CLIENT_SECRET_BASIC;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const WELL_KNOWN_OAUTH_AUTHORIZATION_SERVER: &str = "/.well-known/oauth-authorization-server";
+const WELL_KNOWN_OPENID_CONFIGURATION: &str = "/.well-known/openid-configuration";
+
+/// Kind of discovery document published by an authorization server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetadataKind {
+    /// RFC 8414 `oauth-authorization-server`.
+    OAuth,
+    /// OpenID Connect Discovery `openid-configuration`.
+    OpenId,
+}
+
+impl MetadataKind {
+    /// URL of the discovery document for `issuer`.
+    ///
+    /// RFC 8414 inserts the well-known suffix between the host and the path of
+    /// the issuer, while OpenID Connect appends it to the issuer.
+    pub fn discovery_url(self, issuer: &str) -> Result<String, url::ParseError> {
+        let mut url = url::Url::parse(issuer)?;
+        let path = url.path().trim_end_matches('/').to_string();
+        match self {
+            MetadataKind::OAuth => url.set_path(&format!(
+                "{}{}",
+                WELL_KNOWN_OAUTH_AUTHORIZATION_SERVER, path
+            )),
+            MetadataKind::OpenId => {
+                url.set_path(&format!("{}{}", path, WELL_KNOWN_OPENID_CONFIGURATION))
+            }
+        }
+        Ok(url.into())
+    }
+}
+
+/// Authorization server metadata (RFC 8414 / OpenID Connect Discovery 1.0).
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct AuthorizationServerMetadata {
+    pub issuer: String,
+    #[serde(default)]
+    pub authorization_endpoint: Option<String>,
+    #[serde(default)]
+    pub token_endpoint: Option<String>,
+    #[serde(default)]
+    pub device_authorization_endpoint: Option<String>,
+    #[serde(default)]
+    pub revocation_endpoint: Option<String>,
+    #[serde(default)]
+    pub introspection_endpoint: Option<String>,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+    #[serde(default)]
+    pub registration_endpoint: Option<String>,
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+    #[serde(default)]
+    pub scopes_supported: Vec<String>,
+    #[serde(default)]
+    pub response_types_supported: Vec<String>,
+    #[serde(default)]
+    pub response_modes_supported: Vec<String>,
+    #[serde(default)]
+    pub grant_types_supported: Vec<String>,
+    #[serde(default)]
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+    #[serde(default)]
+    pub token_endpoint_auth_signing_alg_values_supported: Vec<String>,
+    #[serde(default)]
+    pub revocation_endpoint_auth_methods_supported: Vec<String>,
+    #[serde(default)]
+    pub introspection_endpoint_auth_methods_supported: Vec<String>,
+    #[serde(default)]
+    pub code_challenge_methods_supported: Vec<String>,
+    #[serde(default)]
+    pub id_token_signing_alg_values_supported: Vec<String>,
+    #[serde(default)]
+    pub dpop_signing_alg_values_supported: Vec<String>,
+    /// Members not modeled above.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl AuthorizationServerMetadata {
+    pub fn from_slice(body: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(body)
+    }
+
+    /// The `issuer` of the document must be identical to the one used for discovery.
+    pub fn is_issued_by(&self, issuer: &str) -> bool {
+        self.issuer == issuer
+    }
+
+    /// Whether the token endpoint accepts `method` (`client_secret_basic` when omitted).
+    pub fn supports_token_endpoint_auth_method(&self, method: &str) -> bool {
+        supports_auth_method(&self.token_endpoint_auth_methods_supported, method)
+    }
+
+    /// Whether the revocation endpoint accepts `method` (`client_secret_basic` when omitted).
+    pub fn supports_revocation_endpoint_auth_method(&self, method: &str) -> bool {
+        supports_auth_method(&self.revocation_endpoint_auth_methods_supported, method)
+    }
+
+    /// Whether the introspection endpoint accepts `method` (`client_secret_basic` when omitted).
+    pub fn supports_introspection_endpoint_auth_method(&self, method: &str) -> bool {
+        supports_auth_method(&self.introspection_endpoint_auth_methods_supported, method)
+    }
+
+    pub fn supports_code_challenge_method(&self, method: &str) -> bool {
+        self.code_challenge_methods_supported
+            .iter()
+            .any(|m| m == method)
+    }
+
+    pub fn supports_grant_type(&self, grant_type: &str) -> bool {
+        // `authorization_code` and `implicit` are the defaults when omitted.
+        if self.grant_types_supported.is_empty() {
+            grant_type == "authorization_code" || grant_type == "implicit"
+        } else {
+            self.grant_types_supported.iter().any(|g| g == grant_type)
+        }
+    }
+}
+
+fn supports_auth_method(supported: &[String], method: &str) -> bool {
+    if supported.is_empty() {
+        method == OAUTH_VALUE_AUTH_METHOD_CLIENT_SECRET_BASIC
+    } else {
+        supported.iter().any(|m| m == method)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_discovery_url() {
+        assert_eq!(
+            MetadataKind::OAuth
+                .discovery_url("https://server.example.com")
+                .unwrap(),
+            "https://server.example.com/.well-known/oauth-authorization-server"
+        );
+        assert_eq!(
+            MetadataKind::OAuth
+                .discovery_url("https://server.example.com/issuer1")
+                .unwrap(),
+            "https://server.example.com/.well-known/oauth-authorization-server/issuer1"
+        );
+        assert_eq!(
+            MetadataKind::OpenId
+                .discovery_url("https://server.example.com/issuer1/")
+                .unwrap(),
+            "https://server.example.com/issuer1/.well-known/openid-configuration"
+        );
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        // https://tools.ietf.org/html/rfc8414#section-3.2
+        let body = br#"{
+            "issuer": "https://server.example.com",
+            "authorization_endpoint": "https://server.example.com/authorize",
+            "token_endpoint": "https://server.example.com/token",
+            "token_endpoint_auth_methods_supported": ["client_secret_basic", "private_key_jwt"],
+            "token_endpoint_auth_signing_alg_values_supported": ["RS256", "ES256"],
+            "userinfo_endpoint": "https://server.example.com/userinfo",
+            "jwks_uri": "https://server.example.com/jwks.json",
+            "registration_endpoint": "https://server.example.com/register",
+            "scopes_supported": ["openid", "profile", "email", "address", "phone", "offline_access"],
+            "response_types_supported": ["code", "code token"],
+            "service_documentation": "http://server.example.com/service_documentation.html",
+            "ui_locales_supported": ["en-US", "en-GB", "en-CA", "fr-FR", "fr-CA"]
+        }"#;
+        let metadata = AuthorizationServerMetadata::from_slice(body).unwrap();
+        assert!(metadata.is_issued_by("https://server.example.com"));
+        assert!(!metadata.is_issued_by("https://server.example.com/"));
+        assert_eq!(
+            metadata.token_endpoint.as_deref(),
+            Some("https://server.example.com/token")
+        );
+        assert_eq!(metadata.revocation_endpoint, None);
+        assert!(metadata.supports_token_endpoint_auth_method("private_key_jwt"));
+        assert!(!metadata.supports_token_endpoint_auth_method("client_secret_post"));
+        // the revocation and introspection endpoints do not inherit the token endpoint methods.
+        assert!(!metadata.supports_revocation_endpoint_auth_method("private_key_jwt"));
+        assert!(metadata.supports_revocation_endpoint_auth_method("client_secret_basic"));
+        assert!(!metadata.supports_introspection_endpoint_auth_method("private_key_jwt"));
+        assert!(metadata.supports_grant_type("authorization_code"));
+        assert!(!metadata.supports_grant_type("client_credentials"));
+        assert!(metadata.extra.contains_key("service_documentation"));
+    }
+
+    #[test]
+    fn test_default_auth_methods() {
+        let metadata =
+            AuthorizationServerMetadata::from_slice(br#"{"issuer": "https://server.example.com"}"#)
+                .unwrap();
+        assert!(metadata.supports_token_endpoint_auth_method("client_secret_basic"));
+        assert!(!metadata.supports_token_endpoint_auth_method("client_secret_post"));
+        assert!(metadata.supports_introspection_endpoint_auth_method("client_secret_basic"));
+    }
+}
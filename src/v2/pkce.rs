@@ -0,0 +1,54 @@
+use crate::v2::jws::base64url_encode;
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+
+pub const OAUTH_VALUE_CODE_CHALLENGE_METHOD_S256: &str = "S256";
+
+/// PKCE `code_verifier` (RFC 7636).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PkceVerifier<'a> {
+    verifier: Cow<'a, str>,
+}
+
+impl<'a> PkceVerifier<'a> {
+    /// Random verifier of 43 characters, encoding 32 bytes of entropy.
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        PkceVerifier {
+            verifier: Cow::from(base64url_encode(&bytes)),
+        }
+    }
+
+    pub fn new<T: Into<Cow<'a, str>>>(verifier: T) -> Self {
+        PkceVerifier {
+            verifier: verifier.into(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.verifier
+    }
+
+    /// `code_challenge` for the `S256` method.
+    pub fn challenge(&self) -> String {
+        base64url_encode(&Sha256::digest(self.verifier.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_challenge() {
+        // https://tools.ietf.org/html/rfc7636#appendix-B
+        let verifier = PkceVerifier::new("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+        assert_eq!(
+            verifier.challenge(),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        assert_eq!(PkceVerifier::generate().as_str().len(), 43);
+    }
+}
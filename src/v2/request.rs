@@ -0,0 +1,39 @@
+use std::borrow::Cow;
+
+/// Form-encoded POST request to an authorization server endpoint.
+pub struct EndpointRequest<'a> {
+    pub endpoint: Cow<'a, str>,
+    pub headers: Vec<(&'static str, String)>,
+    pub form: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+impl<'a> EndpointRequest<'a> {
+    pub fn new<T: Into<Cow<'a, str>>>(endpoint: T) -> Self {
+        EndpointRequest {
+            endpoint: endpoint.into(),
+            headers: Vec::new(),
+            form: Vec::new(),
+        }
+    }
+
+    pub fn param<TKey, TValue>(&mut self, key: TKey, value: TValue) -> &mut Self
+    where
+        TKey: Into<Cow<'a, str>>,
+        TValue: Into<Cow<'a, str>>,
+    {
+        self.form.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn header<T: Into<String>>(&mut self, name: &'static str, value: T) -> &mut Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    /// `application/x-www-form-urlencoded` request body.
+    pub fn body(&self) -> String {
+        url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(self.form.iter())
+            .finish()
+    }
+}
@@ -0,0 +1,46 @@
+use crate::v2::client_auth::ClientAuthentication;
+use crate::v2::request::EndpointRequest;
+use crate::v2::values::TokenTypeHint;
+use std::borrow::Cow;
+
+const OAUTH_PARAM_KEY_TOKEN: &str = "token";
+const OAUTH_PARAM_KEY_TOKEN_TYPE_HINT: &str = "token_type_hint";
+
+/// Token revocation request (RFC 7009).
+///
+/// The server answers 200 for every valid request, including one for a token
+/// that was already invalid, so only error responses need to be inspected.
+pub struct RevocationRequest<'a> {
+    endpoint: Cow<'a, str>,
+    token: Cow<'a, str>,
+    token_type_hint: Option<TokenTypeHint>,
+}
+
+impl<'a> RevocationRequest<'a> {
+    pub fn new<TEndpoint, TToken>(endpoint: TEndpoint, token: TToken) -> Self
+    where
+        TEndpoint: Into<Cow<'a, str>>,
+        TToken: Into<Cow<'a, str>>,
+    {
+        RevocationRequest {
+            endpoint: endpoint.into(),
+            token: token.into(),
+            token_type_hint: None,
+        }
+    }
+
+    pub fn token_type_hint(&mut self, hint: TokenTypeHint) -> &mut Self {
+        self.token_type_hint = Some(hint);
+        self
+    }
+
+    pub fn build(self, auth: &ClientAuthentication<'a>) -> EndpointRequest<'a> {
+        let mut request = EndpointRequest::new(self.endpoint);
+        request.param(OAUTH_PARAM_KEY_TOKEN, self.token);
+        if let Some(hint) = self.token_type_hint {
+            request.param(OAUTH_PARAM_KEY_TOKEN_TYPE_HINT, <&str>::from(hint));
+        }
+        auth.apply(&mut request);
+        request
+    }
+}
@@ -0,0 +1,175 @@
+use crate::secret::Secret;
+use crate::v2::client_auth::ClientAuthentication;
+use crate::v2::pkce::PkceVerifier;
+use crate::v2::request::EndpointRequest;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+const OAUTH_PARAM_KEY_GRANT_TYPE: &str = "grant_type";
+const OAUTH_PARAM_KEY_CODE: &str = "code";
+const OAUTH_PARAM_KEY_REDIRECT_URI: &str = "redirect_uri";
+const OAUTH_PARAM_KEY_CODE_VERIFIER: &str = "code_verifier";
+const OAUTH_PARAM_KEY_REFRESH_TOKEN: &str = "refresh_token";
+const OAUTH_PARAM_KEY_SCOPE: &str = "scope";
+
+pub const OAUTH_VALUE_GRANT_TYPE_AUTHORIZATION_CODE: &str = "authorization_code";
+pub const OAUTH_VALUE_GRANT_TYPE_REFRESH_TOKEN: &str = "refresh_token";
+
+/// Access token request to the token endpoint.
+///
+/// https://tools.ietf.org/html/rfc6749#section-4.1.3
+pub struct TokenRequest<'a> {
+    endpoint: Cow<'a, str>,
+    params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+impl<'a> TokenRequest<'a> {
+    /// Exchange an authorization `code` received at `redirect_uri`.
+    pub fn authorization_code<TEndpoint, TCode, TRedirectUri>(
+        endpoint: TEndpoint,
+        code: TCode,
+        redirect_uri: TRedirectUri,
+    ) -> Self
+    where
+        TEndpoint: Into<Cow<'a, str>>,
+        TCode: Into<Cow<'a, str>>,
+        TRedirectUri: Into<Cow<'a, str>>,
+    {
+        let mut request = TokenRequest::grant(endpoint, OAUTH_VALUE_GRANT_TYPE_AUTHORIZATION_CODE);
+        request
+            .param(OAUTH_PARAM_KEY_CODE, code)
+            .param(OAUTH_PARAM_KEY_REDIRECT_URI, redirect_uri);
+        request
+    }
+
+    /// https://tools.ietf.org/html/rfc6749#section-6
+    pub fn refresh_token<TEndpoint, TToken>(endpoint: TEndpoint, refresh_token: TToken) -> Self
+    where
+        TEndpoint: Into<Cow<'a, str>>,
+        TToken: Into<Cow<'a, str>>,
+    {
+        let mut request = TokenRequest::grant(endpoint, OAUTH_VALUE_GRANT_TYPE_REFRESH_TOKEN);
+        request.param(OAUTH_PARAM_KEY_REFRESH_TOKEN, refresh_token);
+        request
+    }
+
+    fn grant<T: Into<Cow<'a, str>>>(endpoint: T, grant_type: &'static str) -> Self {
+        TokenRequest {
+            endpoint: endpoint.into(),
+            params: vec![(OAUTH_PARAM_KEY_GRANT_TYPE.into(), grant_type.into())],
+        }
+    }
+
+    /// `code_verifier` of the PKCE bound authorization request.
+    pub fn code_verifier(&mut self, verifier: &PkceVerifier) -> &mut Self {
+        self.param(OAUTH_PARAM_KEY_CODE_VERIFIER, verifier.as_str().to_string())
+    }
+
+    pub fn scope<T: Into<Cow<'a, str>>>(&mut self, scope: T) -> &mut Self {
+        self.param(OAUTH_PARAM_KEY_SCOPE, scope)
+    }
+
+    pub fn param<TKey, TValue>(&mut self, key: TKey, value: TValue) -> &mut Self
+    where
+        TKey: Into<Cow<'a, str>>,
+        TValue: Into<Cow<'a, str>>,
+    {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn build(self, auth: &ClientAuthentication<'a>) -> EndpointRequest<'a> {
+        let mut request = EndpointRequest::new(self.endpoint);
+        for (key, value) in self.params {
+            request.param(key, value);
+        }
+        auth.apply(&mut request);
+        request
+    }
+}
+
+/// Successful response of the token endpoint.
+///
+/// https://tools.ietf.org/html/rfc6749#section-5.1
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: Secret<'static>,
+    pub token_type: String,
+    #[serde(default)]
+    pub expires_in: Option<i64>,
+    #[serde(default)]
+    pub refresh_token: Option<Secret<'static>>,
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// OpenID Connect ID token.
+    #[serde(default)]
+    pub id_token: Option<String>,
+    /// Extension members of the response.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl TokenResponse {
+    pub fn from_slice(body: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(body)
+    }
+
+    /// Expiry of the access token as a UNIX time, given the time it was issued.
+    pub fn expires_at(&self, issued_at: i64) -> Option<i64> {
+        self.expires_in.map(|e| issued_at + e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_authorization_code() {
+        let mut request = TokenRequest::authorization_code(
+            "https://server.example.com/token",
+            "SplxlOBeZQQYbYS6WxSbIA",
+            "https://client.example.com/cb",
+        );
+        request.code_verifier(&PkceVerifier::new("verifier"));
+        let request = request.build(&ClientAuthentication::client_secret_basic(
+            "s6BhdRkqt3",
+            "gX1fBat3bV",
+        ));
+        assert_eq!(
+            request.body(),
+            "grant_type=authorization_code&code=SplxlOBeZQQYbYS6WxSbIA\
+             &redirect_uri=https%3A%2F%2Fclient.example.com%2Fcb&code_verifier=verifier"
+        );
+        assert_eq!(
+            request.headers,
+            vec![(
+                "Authorization",
+                "Basic czZCaGRSa3F0MzpnWDFmQmF0M2JW".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        // https://tools.ietf.org/html/rfc6749#section-5.1
+        let response = TokenResponse::from_slice(
+            br#"{
+                "access_token":"2YotnFZFEjr1zMsicMWpAA",
+                "token_type":"example",
+                "expires_in":3600,
+                "refresh_token":"tGzv3JOkF0XG5Qx2TlKWIA",
+                "example_parameter":"example_value"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(response.access_token.expose(), "2YotnFZFEjr1zMsicMWpAA");
+        assert_eq!(response.expires_at(1_000), Some(4_600));
+        assert_eq!(
+            response.refresh_token.as_ref().map(Secret::expose),
+            Some("tGzv3JOkF0XG5Qx2TlKWIA")
+        );
+        assert!(response.extra.contains_key("example_parameter"));
+    }
+}
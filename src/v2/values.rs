@@ -0,0 +1,70 @@
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+
+// https://tools.ietf.org/html/rfc7523#section-2.2
+pub const OAUTH_VALUE_CLIENT_ASSERTION_TYPE_JWT_BEARER: &str =
+    "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+pub const OAUTH_VALUE_TOKEN_TYPE_HINT_ACCESS_TOKEN: &str = "access_token";
+pub const OAUTH_VALUE_TOKEN_TYPE_HINT_REFRESH_TOKEN: &str = "refresh_token";
+
+/// `token_type_hint` of introspection and revocation requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenTypeHint {
+    AccessToken,
+    RefreshToken,
+}
+
+impl From<TokenTypeHint> for &'static str {
+    fn from(hint: TokenTypeHint) -> Self {
+        match hint {
+            TokenTypeHint::AccessToken => OAUTH_VALUE_TOKEN_TYPE_HINT_ACCESS_TOKEN,
+            TokenTypeHint::RefreshToken => OAUTH_VALUE_TOKEN_TYPE_HINT_REFRESH_TOKEN,
+        }
+    }
+}
+
+/// Error response of an authorization server endpoint.
+///
+/// https://tools.ietf.org/html/rfc6749#section-5.2
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+    #[serde(default)]
+    pub error_description: Option<String>,
+    #[serde(default)]
+    pub error_uri: Option<String>,
+}
+
+impl ErrorResponse {
+    pub fn from_slice(body: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(body)
+    }
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error_description {
+            Some(description) => write!(f, "{}: {}", self.error, description),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+/// `aud` is either a single string or an array of strings.
+pub(crate) fn deserialize_audience<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Audience {
+        Single(String),
+        Multiple(Vec<String>),
+    }
+    Ok(match Option::<Audience>::deserialize(deserializer)? {
+        Some(Audience::Single(aud)) => vec![aud],
+        Some(Audience::Multiple(aud)) => aud,
+        None => Vec::new(),
+    })
+}